@@ -0,0 +1,253 @@
+use glacier_commons::metadata::{ResourceMetadata, ResourceReference, RuntimeID};
+use thiserror::Error;
+use tryvial::try_fn;
+
+use crate::bin1::{Bin1, Bin1Error, Bin1Writer};
+
+#[cfg(feature = "rune")]
+pub fn rune_module() -> Result<rune::Module, rune::ContextError> {
+	let mut module = rune::Module::with_crate_item("glacier_formats", ["asva"])?;
+
+	module.ty::<AsvaError>()?;
+	module.ty::<AnimationSoundVariations>()?;
+	module.ty::<AnimationVariation>()?;
+
+	#[cfg(feature = "sdef")]
+	module.ty::<AnimationSoundLink>()?;
+
+	#[cfg(feature = "sdef")]
+	module.function_meta(AnimationSoundVariations::link_sound_definitions__meta)?;
+
+	Ok(module)
+}
+
+type Result<T, E = AsvaError> = std::result::Result<T, E>;
+
+/// The size of an entry: the animation and sound definition names followed by the variation and padding.
+const ENTRY_SIZE: u64 = 0x28;
+
+#[derive(Error, Debug)]
+#[cfg_attr(feature = "rune", derive(better_rune_derive::Any))]
+#[cfg_attr(feature = "rune", rune(item = ::glacier_formats::asva))]
+#[cfg_attr(feature = "rune", rune_derive(DISPLAY_FMT, DEBUG_FMT))]
+pub enum AsvaError {
+	#[error("invalid BIN1: {0}")]
+	Bin1(#[from] Bin1Error),
+
+	#[error("invalid number: {0}")]
+	InvalidNumber(#[from] std::num::TryFromIntError),
+
+	#[error("expected a single reference to the animation set but found {0}")]
+	UnexpectedReferenceCount(usize),
+
+	#[error("entry at {0:#x} has non-zero padding")]
+	UnexpectedPadding(u64)
+}
+
+/// The sound variations of an animation set; a parsed ASVA file.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "rune", serde_with::apply(_ => #[rune(get, set)]))]
+#[cfg_attr(feature = "rune", derive(better_rune_derive::Any))]
+#[cfg_attr(feature = "rune", rune(item = ::glacier_formats::asva))]
+#[cfg_attr(feature = "rune", rune_derive(DEBUG_FMT, PARTIAL_EQ, EQ, CLONE))]
+#[cfg_attr(feature = "rune", rune(constructor))]
+#[cfg_attr(feature = "rune", rune_functions(Self::parse__meta, Self::generate__meta))]
+pub struct AnimationSoundVariations {
+	pub id: RuntimeID,
+
+	/// The animation set (ASET) these variations belong to. This is the only reference of an ASVA.
+	pub animation_set: ResourceReference,
+
+	pub variations: Vec<AnimationVariation>
+}
+
+/// An animation which triggers a sound definition (such as `Dth_HeadShot` or `Exp_Carry`) with a given variation.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "rune", serde_with::apply(_ => #[rune(get, set)]))]
+#[cfg_attr(feature = "rune", derive(better_rune_derive::Any))]
+#[cfg_attr(feature = "rune", rune(item = ::glacier_formats::asva))]
+#[cfg_attr(feature = "rune", rune_derive(DEBUG_FMT, PARTIAL_EQ, EQ, CLONE))]
+#[cfg_attr(feature = "rune", rune(constructor))]
+pub struct AnimationVariation {
+	pub animation: String,
+	pub sound_definition: String,
+	pub variation: u32
+}
+
+impl AnimationSoundVariations {
+	/// Parse an ASVA.
+	#[try_fn]
+	#[cfg_attr(feature = "rune", rune::function(keep, path = Self::parse))]
+	#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+	pub fn parse(asva_data: &[u8], asva_metadata: &ResourceMetadata) -> Result<Self> {
+		let bin1 = Bin1::parse(asva_data)?;
+
+		let [animation_set] = asva_metadata.references.as_slice() else {
+			return Err(AsvaError::UnexpectedReferenceCount(asva_metadata.references.len()));
+		};
+
+		Self {
+			id: asva_metadata.id,
+			animation_set: animation_set.to_owned(),
+			variations: bin1
+				.read_array(0, ENTRY_SIZE)?
+				.into_iter()
+				.map(|entry| -> Result<_> {
+					if bin1.read_u32(entry + 36)? != 0 {
+						return Err(AsvaError::UnexpectedPadding(entry));
+					}
+
+					Ok(AnimationVariation {
+						animation: bin1.read_string(entry)?,
+						sound_definition: bin1.read_string(entry + 16)?,
+						variation: bin1.read_u32(entry + 32)?
+					})
+				})
+				.collect::<Result<_>>()?
+		}
+	}
+
+	/// Serialise this ASVA.
+	#[try_fn]
+	#[cfg_attr(feature = "rune", rune::function(keep, instance))]
+	#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+	pub fn generate(self) -> Result<(Vec<u8>, ResourceMetadata)> {
+		let mut writer = Bin1Writer::new();
+
+		let entries_start = writer.write_array_header(self.variations.len(), ENTRY_SIZE as usize)?;
+
+		// Strings are laid out after the entries, each aligned to 4 bytes and preceded by its size
+		let mut string_offset = entries_start + ENTRY_SIZE * self.variations.len() as u64;
+		for variation in &self.variations {
+			for string in [&variation.animation, &variation.sound_definition] {
+				writer.write_string_header(string.len(), string_offset + 4)?;
+				string_offset = (string_offset + 4 + string.len() as u64 + 1).next_multiple_of(4);
+			}
+
+			writer.write_u32(variation.variation);
+			writer.write_u32(0);
+		}
+
+		for variation in &self.variations {
+			for string in [&variation.animation, &variation.sound_definition] {
+				writer.write_string_data(string)?;
+				writer.align(4);
+			}
+		}
+
+		(
+			writer.finish(8).generate()?,
+			ResourceMetadata {
+				id: self.id,
+				resource_type: "ASVA".try_into().unwrap(),
+				compressed: ResourceMetadata::infer_compressed("ASVA".try_into().unwrap()),
+				scrambled: ResourceMetadata::infer_scrambled("ASVA".try_into().unwrap()),
+				references: vec![self.animation_set]
+			}
+		)
+	}
+}
+
+/// An animation linked to the sound definition it triggers.
+#[cfg(feature = "sdef")]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "rune", serde_with::apply(_ => #[rune(get, set)]))]
+#[cfg_attr(feature = "rune", derive(better_rune_derive::Any))]
+#[cfg_attr(feature = "rune", rune(item = ::glacier_formats::asva))]
+#[cfg_attr(feature = "rune", rune_derive(DEBUG_FMT, PARTIAL_EQ, EQ, CLONE))]
+pub struct AnimationSoundLink {
+	pub animation: String,
+	pub sound_definition: String,
+	pub variation: u32,
+
+	/// Whether the sound definition exists in the SDEF.
+	pub defined: bool,
+
+	/// The dialogue (DLGE) played for the sound definition, if it has one.
+	pub dialogue: Option<RuntimeID>
+}
+
+#[cfg(feature = "sdef")]
+impl AnimationSoundVariations {
+	/// Link each animation to the sound definition it triggers in the given SDEF.
+	#[cfg_attr(feature = "rune", rune::function(keep, instance))]
+	pub fn link_sound_definitions(&self, sdef: &crate::sdef::SoundDefinitions) -> Vec<AnimationSoundLink> {
+		self.variations
+			.iter()
+			.map(|variation| {
+				let definition = sdef.definitions.get(&variation.sound_definition);
+
+				AnimationSoundLink {
+					animation: variation.animation.to_owned(),
+					sound_definition: variation.sound_definition.to_owned(),
+					variation: variation.variation,
+					defined: definition.is_some(),
+					dialogue: definition.copied().flatten()
+				}
+			})
+			.collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use glacier_commons::metadata::{ReferenceFlags, ReferenceType};
+
+	use super::*;
+
+	fn variations() -> AnimationSoundVariations {
+		AnimationSoundVariations {
+			id: "00C7A0B5E2D4F1A3".parse().unwrap(),
+			animation_set: ResourceReference {
+				resource: "00F2E1D0C3B4A596".parse().unwrap(),
+				flags: ReferenceFlags {
+					reference_type: ReferenceType::Normal,
+					acquired: true,
+					language_code: 0b0001_1111
+				}
+			},
+			variations: vec![
+				AnimationVariation {
+					animation: "Dth_HeadShot".into(),
+					sound_definition: "Dth_HeadShot".into(),
+					variation: 2
+				},
+				AnimationVariation {
+					animation: "Exp_Carry_Body_Lift".into(),
+					sound_definition: "Exp_Carry".into(),
+					variation: 0
+				},
+			]
+		}
+	}
+
+	#[test]
+	fn round_trip() {
+		let (data, metadata) = variations().generate().unwrap();
+		let parsed = AnimationSoundVariations::parse(&data, &metadata).unwrap();
+
+		assert_eq!(metadata.references, vec![variations().animation_set]);
+		assert_eq!(parsed, variations());
+		assert_eq!(parsed.generate().unwrap().0, data);
+	}
+
+	#[test]
+	fn requires_a_single_reference() {
+		let (data, mut metadata) = variations().generate().unwrap();
+
+		metadata.references.push(metadata.references[0].to_owned());
+		assert!(matches!(
+			AnimationSoundVariations::parse(&data, &metadata),
+			Err(AsvaError::UnexpectedReferenceCount(2))
+		));
+
+		metadata.references.clear();
+		assert!(matches!(
+			AnimationSoundVariations::parse(&data, &metadata),
+			Err(AsvaError::UnexpectedReferenceCount(0))
+		));
+	}
+}
@@ -0,0 +1,486 @@
+//! The BIN1 container used by ORES and other serialised game types.
+//!
+//! A BIN1 file is a 16-byte header, a data section in which pointers are stored as 64-bit offsets from the start of the
+//! data section, and a list of segments. The relocation segment lists every offset in the data section that holds a
+//! pointer.
+
+use thiserror::Error;
+use tryvial::try_fn;
+
+type Result<T, E = Bin1Error> = std::result::Result<T, E>;
+
+const MAGIC: &[u8; 4] = b"BIN1";
+const RELOCATION_SEGMENT: u32 = 0x12EBA5ED;
+
+/// The flag set on the length of every string in the data section.
+const STRING_FLAG: u32 = 0x40000000;
+
+#[derive(Error, Debug)]
+pub enum Bin1Error {
+	#[error("invalid number: {0}")]
+	InvalidNumber(#[from] std::num::TryFromIntError),
+
+	#[error("invalid UTF-8: {0}")]
+	InvalidString(#[from] std::string::FromUtf8Error),
+
+	#[error("not a BIN1 file")]
+	BadMagic,
+
+	#[error("offset {0:#x} is outside the data section")]
+	OffsetOutOfRange(u64),
+
+	#[error("segment at {0:#x} extends past the end of the file")]
+	TruncatedSegment(usize),
+
+	#[error("array at {0:#x} has a size that isn't a multiple of its element size")]
+	MisalignedArray(u64),
+
+	#[error("array elements must have a non-zero size")]
+	ZeroElementSize,
+
+	#[error("header declares {expected} segments but {found} were found")]
+	MismatchedSegmentCount { expected: u8, found: usize },
+
+	#[error("relocation segment of {size} bytes doesn't hold {count} relocations")]
+	InvalidRelocationSegment { size: usize, count: usize }
+}
+
+/// A BIN1 segment other than the relocation segment, kept as-is.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Bin1Segment {
+	pub id: u32,
+	pub data: Vec<u8>
+}
+
+/// A parsed BIN1 container.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Bin1 {
+	pub alignment: u8,
+
+	/// The data section. Pointers within it are offsets from its start.
+	pub data: Vec<u8>,
+
+	/// The offsets in the data section at which pointers are stored.
+	pub relocations: Vec<u32>,
+
+	pub segments: Vec<Bin1Segment>
+}
+
+impl Bin1 {
+	/// Parse a BIN1 container.
+	#[try_fn]
+	#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+	pub fn parse(bin_data: &[u8]) -> Result<Self> {
+		if bin_data.len() < 16 || &bin_data[0..4] != MAGIC {
+			return Err(Bin1Error::BadMagic);
+		}
+
+		let alignment = bin_data[5];
+		let segment_count = bin_data[6];
+		let data_size = usize::try_from(u32::from_be_bytes(bin_data[8..12].try_into().unwrap()))?;
+
+		let data = bin_data
+			.get(16..16 + data_size)
+			.ok_or(Bin1Error::OffsetOutOfRange(data_size as u64))?
+			.to_vec();
+
+		let mut relocations = vec![];
+		let mut segments = vec![];
+		let mut found_segments = 0;
+
+		let mut position = 16 + data_size;
+		while position + 8 <= bin_data.len() {
+			let id = u32::from_le_bytes(bin_data[position..position + 4].try_into().unwrap());
+			let size = usize::try_from(u32::from_le_bytes(
+				bin_data[position + 4..position + 8].try_into().unwrap()
+			))?;
+
+			let segment = bin_data
+				.get(position + 8..position + 8 + size)
+				.ok_or(Bin1Error::TruncatedSegment(position))?;
+
+			if id == RELOCATION_SEGMENT {
+				let count = usize::try_from(u32::from_le_bytes(
+					segment
+						.get(0..4)
+						.ok_or(Bin1Error::TruncatedSegment(position))?
+						.try_into()
+						.unwrap()
+				))?;
+
+				if count.checked_mul(4).and_then(|x| x.checked_add(4)) != Some(size) {
+					return Err(Bin1Error::InvalidRelocationSegment { size, count });
+				}
+
+				for i in 0..count {
+					let offset = u32::from_le_bytes(
+						segment
+							.get(4 + i * 4..8 + i * 4)
+							.ok_or(Bin1Error::TruncatedSegment(position))?
+							.try_into()
+							.unwrap()
+					);
+
+					if offset as usize + 8 > data.len() {
+						return Err(Bin1Error::OffsetOutOfRange(offset.into()));
+					}
+
+					relocations.push(offset);
+				}
+			} else {
+				segments.push(Bin1Segment {
+					id,
+					data: segment.to_vec()
+				});
+			}
+
+			position += 8 + size;
+			found_segments += 1;
+		}
+
+		if found_segments != usize::from(segment_count) {
+			return Err(Bin1Error::MismatchedSegmentCount {
+				expected: segment_count,
+				found: found_segments
+			});
+		}
+
+		Self {
+			alignment,
+			data,
+			relocations,
+			segments
+		}
+	}
+
+	/// Generate the BIN1 file.
+	#[try_fn]
+	#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+	pub fn generate(&self) -> Result<Vec<u8>> {
+		let mut bin = vec![];
+
+		bin.extend_from_slice(MAGIC);
+		bin.extend_from_slice(&[0, self.alignment, u8::try_from(self.segments.len() + 1)?, 0]);
+		bin.extend_from_slice(&u32::try_from(self.data.len())?.to_be_bytes());
+		bin.extend_from_slice(&[0; 4]);
+		bin.extend_from_slice(&self.data);
+
+		bin.extend_from_slice(&RELOCATION_SEGMENT.to_le_bytes());
+		bin.extend_from_slice(&u32::try_from(4 + self.relocations.len() * 4)?.to_le_bytes());
+		bin.extend_from_slice(&u32::try_from(self.relocations.len())?.to_le_bytes());
+
+		for relocation in &self.relocations {
+			bin.extend_from_slice(&relocation.to_le_bytes());
+		}
+
+		for segment in &self.segments {
+			bin.extend_from_slice(&segment.id.to_le_bytes());
+			bin.extend_from_slice(&u32::try_from(segment.data.len())?.to_le_bytes());
+			bin.extend_from_slice(&segment.data);
+		}
+
+		bin
+	}
+
+	#[try_fn]
+	pub fn read_bytes(&self, offset: u64, len: u64) -> Result<&[u8]> {
+		let start = usize::try_from(offset)?;
+		let end = start
+			.checked_add(usize::try_from(len)?)
+			.ok_or(Bin1Error::OffsetOutOfRange(offset))?;
+
+		self.data.get(start..end).ok_or(Bin1Error::OffsetOutOfRange(offset))?
+	}
+
+	#[try_fn]
+	pub fn read_u32(&self, offset: u64) -> Result<u32> {
+		u32::from_le_bytes(self.read_bytes(offset, 4)?.try_into().unwrap())
+	}
+
+	#[try_fn]
+	pub fn read_u64(&self, offset: u64) -> Result<u64> {
+		u64::from_le_bytes(self.read_bytes(offset, 8)?.try_into().unwrap())
+	}
+
+	/// Read a pointer, checking that it points inside the data section.
+	#[try_fn]
+	pub fn read_pointer(&self, offset: u64) -> Result<u64> {
+		let pointer = self.read_u64(offset)?;
+
+		if pointer > self.data.len() as u64 {
+			return Err(Bin1Error::OffsetOutOfRange(pointer));
+		}
+
+		pointer
+	}
+
+	/// Read a string (length, padding and pointer to the characters).
+	#[try_fn]
+	pub fn read_string(&self, offset: u64) -> Result<String> {
+		String::from_utf8(self.read_string_bytes(offset)?.to_vec())?
+	}
+
+	/// Read the characters of a string as bytes, for strings which hold binary data.
+	#[try_fn]
+	pub fn read_string_bytes(&self, offset: u64) -> Result<&[u8]> {
+		let len = self.read_u32(offset)? & !STRING_FLAG;
+		let pointer = self.read_pointer(offset + 8)?;
+
+		self.read_bytes(pointer, len.into())?
+	}
+
+	/// Read the begin and end pointers of an array.
+	#[try_fn]
+	pub fn read_array_range(&self, offset: u64) -> Result<(u64, u64)> {
+		let begin = self.read_pointer(offset)?;
+		let end = self.read_pointer(offset + 8)?;
+
+		if end < begin {
+			return Err(Bin1Error::MisalignedArray(offset));
+		}
+
+		(begin, end)
+	}
+
+	/// Read an array (begin, end and allocation end pointers), returning the offset of each element.
+	#[try_fn]
+	pub fn read_array(&self, offset: u64, element_size: u64) -> Result<Vec<u64>> {
+		if element_size == 0 {
+			return Err(Bin1Error::ZeroElementSize);
+		}
+
+		let (begin, end) = self.read_array_range(offset)?;
+
+		if (end - begin) % element_size != 0 {
+			return Err(Bin1Error::MisalignedArray(offset));
+		}
+
+		(begin..end).step_by(usize::try_from(element_size)?).collect()
+	}
+
+	/// Whether the given offset in the data section holds a pointer.
+	pub fn is_pointer(&self, offset: u64) -> bool {
+		u32::try_from(offset).is_ok_and(|offset| self.relocations.contains(&offset))
+	}
+}
+
+/// Writes a BIN1 data section, recording relocations for each pointer written.
+#[derive(Clone, Debug, Default)]
+pub struct Bin1Writer {
+	data: Vec<u8>,
+	relocations: Vec<u32>
+}
+
+impl Bin1Writer {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// The current offset in the data section.
+	pub fn position(&self) -> u64 {
+		self.data.len() as u64
+	}
+
+	pub fn write_bytes(&mut self, bytes: &[u8]) {
+		self.data.extend_from_slice(bytes);
+	}
+
+	pub fn write_u32(&mut self, value: u32) {
+		self.data.extend_from_slice(&value.to_le_bytes());
+	}
+
+	pub fn write_u64(&mut self, value: u64) {
+		self.data.extend_from_slice(&value.to_le_bytes());
+	}
+
+	/// Pad the data section with zeroes to a multiple of `alignment`.
+	pub fn align(&mut self, alignment: usize) {
+		self.data.resize(self.data.len().next_multiple_of(alignment), 0);
+	}
+
+	/// Write a pointer to an offset in the data section.
+	#[try_fn]
+	pub fn write_pointer(&mut self, target: u64) -> Result<()> {
+		self.relocations.push(u32::try_from(self.data.len())?);
+		self.write_u64(target);
+	}
+
+	/// Write a string header (length, padding and pointer to the characters).
+	#[try_fn]
+	pub fn write_string_header(&mut self, len: usize, target: u64) -> Result<()> {
+		self.write_u32(u32::try_from(len)? | STRING_FLAG);
+		self.write_u32(0);
+		self.write_pointer(target)?;
+	}
+
+	/// Write the characters of a string, preceded by their size and followed by a null terminator. Returns the offset
+	/// of the characters, for use with [`Self::write_string_header`].
+	#[try_fn]
+	pub fn write_string_data(&mut self, value: &str) -> Result<u64> {
+		self.write_string_bytes(value.as_bytes())?
+	}
+
+	/// Write the characters of a string which holds binary data, as with [`Self::write_string_data`].
+	#[try_fn]
+	pub fn write_string_bytes(&mut self, value: &[u8]) -> Result<u64> {
+		self.write_u32(u32::try_from(value.len() + 1)?);
+
+		let position = self.position();
+		self.write_bytes(value);
+		self.write_bytes(&[0]);
+
+		position
+	}
+
+	/// Write the pointers of an array whose elements are written elsewhere.
+	#[try_fn]
+	pub fn write_array_pointers(&mut self, begin: u64, end: u64) -> Result<()> {
+		self.write_pointer(begin)?;
+		self.write_pointer(end)?;
+		self.write_pointer(end)?;
+	}
+
+	/// Overwrite a previously written pointer with a new target.
+	#[try_fn]
+	pub fn patch_pointer(&mut self, offset: u64, target: u64) -> Result<()> {
+		let offset = usize::try_from(offset)?;
+
+		self.data
+			.get_mut(offset..offset + 8)
+			.ok_or(Bin1Error::OffsetOutOfRange(offset as u64))?
+			.copy_from_slice(&target.to_le_bytes());
+	}
+
+	/// Overwrite the pointers of a previously written array with its actual elements.
+	#[try_fn]
+	pub fn patch_array_pointers(&mut self, offset: u64, begin: u64, end: u64) -> Result<()> {
+		self.patch_pointer(offset, begin)?;
+		self.patch_pointer(offset + 8, end)?;
+		self.patch_pointer(offset + 16, end)?;
+	}
+
+	/// Write the element count of an array, padded such that the elements begin at the next 16-byte boundary. Returns
+	/// the offset at which the elements should be written.
+	#[try_fn]
+	pub fn write_array_count(&mut self, count: usize) -> Result<u64> {
+		let begin = (self.position() + 4).next_multiple_of(16);
+
+		self.data.resize(usize::try_from(begin)? - 4, 0);
+		self.write_u32(u32::try_from(count)?);
+
+		begin
+	}
+
+	/// Write an array header (begin, end and allocation end pointers) followed by the element count, such that the
+	/// elements begin at the next 16-byte boundary. Returns the offset at which the elements should be written.
+	#[try_fn]
+	pub fn write_array_header(&mut self, count: usize, element_size: usize) -> Result<u64> {
+		// The element count is stored immediately before the elements
+		let begin = (self.position() + 24 + 4).next_multiple_of(16);
+		let end = begin + (count * element_size) as u64;
+
+		self.write_array_pointers(begin, end)?;
+		self.write_array_count(count)?
+	}
+
+	/// Finish writing, producing a BIN1 container with the given alignment.
+	pub fn finish(self, alignment: u8) -> Bin1 {
+		Bin1 {
+			alignment,
+			data: self.data,
+			relocations: self.relocations,
+			segments: vec![]
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn bin1() -> Bin1 {
+		let mut writer = Bin1Writer::new();
+
+		let begin = writer.write_array_header(2, 16).unwrap();
+		writer.write_string_header(5, 0).unwrap();
+		writer.write_string_header(0, 0).unwrap();
+
+		for (header, value) in [begin, begin + 16].into_iter().zip(["hello", ""]) {
+			let position = writer.write_string_data(value).unwrap();
+			writer.patch_pointer(header + 8, position).unwrap();
+		}
+
+		let mut bin1 = writer.finish(8);
+
+		bin1.segments.push(Bin1Segment {
+			id: 0x3989BF9F,
+			data: vec![1, 2, 3, 4]
+		});
+
+		bin1
+	}
+
+	#[test]
+	fn round_trip() {
+		let data = bin1().generate().unwrap();
+		let parsed = Bin1::parse(&data).unwrap();
+
+		assert_eq!(parsed, bin1());
+		assert_eq!(parsed.generate().unwrap(), data);
+
+		let elements = parsed.read_array(0, 16).unwrap();
+		assert_eq!(elements.len(), 2);
+		assert_eq!(parsed.read_string(elements[0]).unwrap(), "hello");
+		assert_eq!(parsed.read_string(elements[1]).unwrap(), "");
+		assert!(parsed.is_pointer(0));
+		assert!(!parsed.is_pointer(4));
+	}
+
+	#[test]
+	fn zero_element_size() {
+		assert!(matches!(bin1().read_array(0, 0), Err(Bin1Error::ZeroElementSize)));
+	}
+
+	#[test]
+	fn malformed_header() {
+		let data = bin1().generate().unwrap();
+
+		assert!(matches!(Bin1::parse(b"BIN1"), Err(Bin1Error::BadMagic)));
+		assert!(matches!(
+			Bin1::parse(&[b"BIN2", &data[4..]].concat()),
+			Err(Bin1Error::BadMagic)
+		));
+
+		// Data section larger than the file
+		let mut oversized = data.to_owned();
+		oversized[8..12].copy_from_slice(&u32::MAX.to_be_bytes());
+		assert!(matches!(Bin1::parse(&oversized), Err(Bin1Error::OffsetOutOfRange(_))));
+
+		let mut wrong_count = data.to_owned();
+		wrong_count[6] = 3;
+		assert!(matches!(
+			Bin1::parse(&wrong_count),
+			Err(Bin1Error::MismatchedSegmentCount { expected: 3, found: 2 })
+		));
+
+		assert!(matches!(
+			Bin1::parse(&data[..data.len() - 1]),
+			Err(Bin1Error::TruncatedSegment(_))
+		));
+	}
+
+	#[test]
+	fn relocation_segment_size() {
+		let mut data = bin1().generate().unwrap();
+		let segment = 16 + bin1().data.len();
+
+		// Claim one fewer relocation than the segment holds
+		let count = u32::from_le_bytes(data[segment + 8..segment + 12].try_into().unwrap());
+		data[segment + 8..segment + 12].copy_from_slice(&(count - 1).to_le_bytes());
+
+		assert!(matches!(
+			Bin1::parse(&data),
+			Err(Bin1Error::InvalidRelocationSegment { .. })
+		));
+	}
+}
@@ -0,0 +1,197 @@
+use std::io::{Cursor, Read};
+
+use glacier_commons::metadata::{ResourceMetadata, RuntimeID};
+use indexmap::IndexMap;
+use thiserror::Error;
+use tryvial::try_fn;
+
+#[cfg(feature = "rune")]
+pub fn rune_module() -> Result<rune::Module, rune::ContextError> {
+	let mut module = rune::Module::with_crate_item("glacier_formats", ["clng"])?;
+
+	module.ty::<ClngError>()?;
+	module.ty::<LanguageConfiguration>()?;
+	module.ty::<LanguageMap>()?;
+
+	Ok(module)
+}
+
+type Result<T, E = ClngError> = std::result::Result<T, E>;
+
+#[derive(Error, Debug)]
+#[cfg_attr(feature = "rune", derive(better_rune_derive::Any))]
+#[cfg_attr(feature = "rune", rune(item = ::glacier_formats::clng))]
+#[cfg_attr(feature = "rune", rune_derive(DISPLAY_FMT, DEBUG_FMT))]
+pub enum ClngError {
+	#[error("seek error: {0}")]
+	Seek(#[from] std::io::Error),
+
+	#[error("invalid number: {0}")]
+	InvalidNumber(#[from] std::num::TryFromIntError),
+
+	#[error("invalid utf-8: {0}")]
+	InvalidString(#[from] std::string::FromUtf8Error),
+
+	#[error("language at {0:#x} isn't null-terminated")]
+	MissingNullTerminator(u64)
+}
+
+/// A language configuration; a parsed CLNG file.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "rune", derive(better_rune_derive::Any))]
+#[cfg_attr(feature = "rune", rune(item = ::glacier_formats::clng))]
+#[cfg_attr(feature = "rune", rune_derive(DEBUG_FMT, PARTIAL_EQ, EQ, CLONE))]
+#[cfg_attr(feature = "rune", rune_functions(Self::parse__meta, Self::generate__meta))]
+#[cfg_attr(feature = "rune", rune(install_with = Self::rune_install))]
+#[cfg_attr(feature = "rune", rune(constructor_fn = Self::rune_construct))]
+pub struct LanguageConfiguration {
+	#[cfg_attr(feature = "rune", rune(get, set))]
+	pub id: RuntimeID,
+
+	/// The available languages, in order, and whether each has dubbed audio.
+	pub languages: IndexMap<String, bool>
+}
+
+#[cfg(feature = "rune")]
+crate::rune_map::ordered_map!(
+	/// The languages of a CLNG and whether each has dubbed audio, in order.
+	LanguageMap,
+	String,
+	bool,
+	::glacier_formats::clng
+);
+
+#[cfg(feature = "rune")]
+impl LanguageConfiguration {
+	fn rune_construct(id: RuntimeID, languages: LanguageMap) -> Self {
+		Self {
+			id,
+			languages: languages.0
+		}
+	}
+
+	fn rune_install(module: &mut rune::Module) -> Result<(), rune::ContextError> {
+		module.field_function(&rune::runtime::Protocol::GET, "languages", |s: &Self| {
+			LanguageMap(s.languages.to_owned())
+		})?;
+
+		module.field_function(
+			&rune::runtime::Protocol::SET,
+			"languages",
+			|s: &mut Self, languages: LanguageMap| {
+				s.languages = languages.0;
+			}
+		)?;
+
+		Ok(())
+	}
+}
+
+impl LanguageConfiguration {
+	/// Parse a CLNG.
+	#[try_fn]
+	#[cfg_attr(feature = "rune", rune::function(keep, path = Self::parse))]
+	#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+	pub fn parse(clng_data: &[u8], clng_metadata: &ResourceMetadata) -> Result<Self> {
+		let mut clng = Cursor::new(clng_data);
+
+		let mut languages = IndexMap::new();
+
+		while clng.position() < clng_data.len() as u64 {
+			let position = clng.position();
+
+			let dubbed = {
+				let mut x = [0u8; 1];
+				clng.read_exact(&mut x)?;
+				x[0] != 0
+			};
+
+			let length = u32::from_le_bytes({
+				let mut x = [0u8; 4];
+				clng.read_exact(&mut x)?;
+				x
+			});
+
+			// The length includes the null terminator
+			let mut language = vec![0; usize::try_from(length)?];
+			clng.read_exact(&mut language)?;
+
+			if language.pop() != Some(0) {
+				return Err(ClngError::MissingNullTerminator(position));
+			}
+
+			languages.insert(String::from_utf8(language)?, dubbed);
+		}
+
+		Self {
+			id: clng_metadata.id,
+			languages
+		}
+	}
+
+	/// Serialise this CLNG.
+	#[try_fn]
+	#[cfg_attr(feature = "rune", rune::function(keep, instance))]
+	#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+	pub fn generate(self) -> Result<(Vec<u8>, ResourceMetadata)> {
+		let mut clng = vec![];
+
+		for (language, dubbed) in self.languages {
+			clng.push(dubbed as u8);
+			clng.extend_from_slice(&u32::try_from(language.len() + 1)?.to_le_bytes());
+			clng.extend_from_slice(language.as_bytes());
+			clng.push(0);
+		}
+
+		(
+			clng,
+			ResourceMetadata {
+				id: self.id,
+				resource_type: "CLNG".try_into().unwrap(),
+				compressed: ResourceMetadata::infer_compressed("CLNG".try_into().unwrap()),
+				scrambled: ResourceMetadata::infer_scrambled("CLNG".try_into().unwrap()),
+				references: vec![]
+			}
+		)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn metadata() -> ResourceMetadata {
+		ResourceMetadata {
+			id: "00A43D8DA5F5B5C6".parse().unwrap(),
+			resource_type: "CLNG".try_into().unwrap(),
+			compressed: ResourceMetadata::infer_compressed("CLNG".try_into().unwrap()),
+			scrambled: ResourceMetadata::infer_scrambled("CLNG".try_into().unwrap()),
+			references: vec![]
+		}
+	}
+
+	#[test]
+	fn round_trip() {
+		let clng = LanguageConfiguration {
+			id: metadata().id,
+			languages: IndexMap::from([("xx".into(), false), ("en".into(), true), ("jp".into(), false)])
+		};
+
+		let (data, metadata) = clng.clone().generate().unwrap();
+		let parsed = LanguageConfiguration::parse(&data, &metadata).unwrap();
+
+		assert_eq!(parsed, clng);
+		assert_eq!(parsed.generate().unwrap().0, data);
+	}
+
+	#[test]
+	fn missing_null_terminator() {
+		let data = [1, 3, 0, 0, 0, b'e', b'n', b'x'];
+
+		assert!(matches!(
+			LanguageConfiguration::parse(&data, &metadata()),
+			Err(ClngError::MissingNullTerminator(0))
+		));
+	}
+}
@@ -0,0 +1,242 @@
+use std::io::{Cursor, Read};
+
+use glacier_commons::metadata::{ResourceMetadata, ResourceReference, RuntimeID};
+use indexmap::IndexMap;
+use thiserror::Error;
+use tryvial::try_fn;
+
+#[cfg(feature = "rune")]
+pub fn rune_module() -> Result<rune::Module, rune::ContextError> {
+	let mut module = rune::Module::with_crate_item("glacier_formats", ["ditl"])?;
+
+	module.ty::<DitlError>()?;
+	module.ty::<SoundTagList>()?;
+	module.ty::<SoundTagMap>()?;
+
+	Ok(module)
+}
+
+type Result<T, E = DitlError> = std::result::Result<T, E>;
+
+#[derive(Error, Debug)]
+#[cfg_attr(feature = "rune", derive(better_rune_derive::Any))]
+#[cfg_attr(feature = "rune", rune(item = ::glacier_formats::ditl))]
+#[cfg_attr(feature = "rune", rune_derive(DISPLAY_FMT, DEBUG_FMT))]
+pub enum DitlError {
+	#[error("seek error: {0}")]
+	Seek(#[from] std::io::Error),
+
+	#[error("invalid number: {0}")]
+	InvalidNumber(#[from] std::num::TryFromIntError),
+
+	#[error("no such dependency index: {0}")]
+	InvalidDependency(usize),
+
+	#[error("sound tag {0:#010x} appears more than once")]
+	DuplicateSoundTag(u32)
+}
+
+/// A sound tag list; a parsed DITL file.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "rune", derive(better_rune_derive::Any))]
+#[cfg_attr(feature = "rune", rune(item = ::glacier_formats::ditl))]
+#[cfg_attr(feature = "rune", rune_derive(DEBUG_FMT, PARTIAL_EQ, EQ, CLONE))]
+#[cfg_attr(feature = "rune", rune_functions(Self::parse__meta, Self::generate__meta))]
+#[cfg_attr(feature = "rune", rune(install_with = Self::rune_install))]
+#[cfg_attr(feature = "rune", rune(constructor_fn = Self::rune_construct))]
+pub struct SoundTagList {
+	#[cfg_attr(feature = "rune", rune(get, set))]
+	pub id: RuntimeID,
+
+	/// The resource (DLGE or SDEF) played for each sound tag along with its reference flags, keyed by the CRC32 of the
+	/// sound tag's name.
+	pub sound_tags: IndexMap<u32, ResourceReference>
+}
+
+#[cfg(feature = "rune")]
+crate::rune_map::ordered_map!(
+	/// The sound tags of a DITL by hash, in order.
+	SoundTagMap,
+	u32,
+	ResourceReference,
+	::glacier_formats::ditl
+);
+
+#[cfg(feature = "rune")]
+impl SoundTagList {
+	fn rune_construct(id: RuntimeID, sound_tags: SoundTagMap) -> Self {
+		Self {
+			id,
+			sound_tags: sound_tags.0
+		}
+	}
+
+	fn rune_install(module: &mut rune::Module) -> Result<(), rune::ContextError> {
+		module.field_function(&rune::runtime::Protocol::GET, "sound_tags", |s: &Self| {
+			SoundTagMap(s.sound_tags.to_owned())
+		})?;
+
+		module.field_function(
+			&rune::runtime::Protocol::SET,
+			"sound_tags",
+			|s: &mut Self, sound_tags: SoundTagMap| {
+				s.sound_tags = sound_tags.0;
+			}
+		)?;
+
+		Ok(())
+	}
+}
+
+impl SoundTagList {
+	/// Parse a DITL. Sound tags are keyed by hash, so a DITL with a repeated hash is rejected rather than losing entries.
+	#[try_fn]
+	#[cfg_attr(feature = "rune", rune::function(keep, path = Self::parse))]
+	#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+	pub fn parse(ditl_data: &[u8], ditl_metadata: &ResourceMetadata) -> Result<Self> {
+		let mut ditl = Cursor::new(ditl_data);
+
+		let count = u32::from_le_bytes({
+			let mut x = [0u8; 4];
+			ditl.read_exact(&mut x)?;
+			x
+		});
+
+		let mut sound_tags = IndexMap::with_capacity(usize::try_from(count)?);
+
+		for _ in 0..count {
+			let dependency_index = usize::try_from(u32::from_le_bytes({
+				let mut x = [0u8; 4];
+				ditl.read_exact(&mut x)?;
+				x
+			}))?;
+
+			let hash = u32::from_le_bytes({
+				let mut x = [0u8; 4];
+				ditl.read_exact(&mut x)?;
+				x
+			});
+
+			let reference = ditl_metadata
+				.references
+				.get(dependency_index)
+				.ok_or(DitlError::InvalidDependency(dependency_index))?
+				.to_owned();
+
+			if sound_tags.insert(hash, reference).is_some() {
+				return Err(DitlError::DuplicateSoundTag(hash));
+			}
+		}
+
+		Self {
+			id: ditl_metadata.id,
+			sound_tags
+		}
+	}
+
+	/// Serialise this DITL.
+	#[try_fn]
+	#[cfg_attr(feature = "rune", rune::function(keep, instance))]
+	#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+	pub fn generate(self) -> Result<(Vec<u8>, ResourceMetadata)> {
+		let mut ditl = vec![];
+		let mut references = vec![];
+
+		ditl.extend_from_slice(&u32::try_from(self.sound_tags.len())?.to_le_bytes());
+
+		for (hash, reference) in self.sound_tags {
+			ditl.extend_from_slice(&u32::try_from(references.len())?.to_le_bytes());
+			ditl.extend_from_slice(&hash.to_le_bytes());
+
+			references.push(reference);
+		}
+
+		(
+			ditl,
+			ResourceMetadata {
+				id: self.id,
+				resource_type: "DITL".try_into().unwrap(),
+				compressed: ResourceMetadata::infer_compressed("DITL".try_into().unwrap()),
+				scrambled: ResourceMetadata::infer_scrambled("DITL".try_into().unwrap()),
+				references
+			}
+		)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use glacier_commons::metadata::{ReferenceFlags, ReferenceType};
+
+	use super::*;
+
+	fn reference(resource: &str, flags: ReferenceFlags) -> ResourceReference {
+		ResourceReference {
+			resource: resource.parse().unwrap(),
+			flags
+		}
+	}
+
+	#[test]
+	fn round_trip() {
+		let ditl = SoundTagList {
+			id: "00E51F2E27A7C1BA".parse().unwrap(),
+			sound_tags: IndexMap::from([
+				(0x2A6B_44F1, reference("0046B11A2F3C7C58", ReferenceFlags::default())),
+				(
+					0x0000_0010,
+					reference(
+						"003F9D6A4C8E2B10",
+						ReferenceFlags {
+							reference_type: ReferenceType::Weak,
+							acquired: true,
+							language_code: 3
+						}
+					)
+				),
+				(
+					0x9E3D_77A2,
+					reference(
+						"0046B11A2F3C7C58",
+						ReferenceFlags {
+							reference_type: ReferenceType::Install,
+							acquired: false,
+							language_code: 0
+						}
+					)
+				)
+			])
+		};
+
+		let (data, metadata) = ditl.clone().generate().unwrap();
+		let parsed = SoundTagList::parse(&data, &metadata).unwrap();
+
+		assert_eq!(
+			metadata.references,
+			ditl.sound_tags.values().cloned().collect::<Vec<_>>()
+		);
+		assert_eq!(parsed, ditl);
+
+		let (regenerated, regenerated_metadata) = parsed.generate().unwrap();
+		assert_eq!(regenerated, data);
+		assert_eq!(regenerated_metadata.references, metadata.references);
+	}
+
+	#[test]
+	fn duplicate_sound_tag() {
+		let (_, metadata) = SoundTagList {
+			id: "00E51F2E27A7C1BA".parse().unwrap(),
+			sound_tags: IndexMap::from([(1, reference("0046B11A2F3C7C58", ReferenceFlags::default()))])
+		}
+		.generate()
+		.unwrap();
+
+		let data = [2, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0];
+
+		assert!(matches!(
+			SoundTagList::parse(&data, &metadata),
+			Err(DitlError::DuplicateSoundTag(1))
+		));
+	}
+}
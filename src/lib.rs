@@ -1,3 +1,20 @@
+#[cfg(all(
+	feature = "rune",
+	any(
+		feature = "material",
+		feature = "ores",
+		feature = "sdef",
+		feature = "locr",
+		feature = "ditl",
+		feature = "clng",
+		feature = "rtlv"
+	)
+))]
+mod rune_map;
+
+#[cfg(feature = "bin1")]
+pub mod bin1;
+
 #[cfg(feature = "material")]
 pub mod material;
 
@@ -13,6 +30,24 @@ pub mod sdef;
 #[cfg(feature = "texture")]
 pub mod texture;
 
+#[cfg(feature = "locr")]
+pub mod locr;
+
+#[cfg(feature = "ditl")]
+pub mod ditl;
+
+#[cfg(feature = "clng")]
+pub mod clng;
+
+#[cfg(feature = "rtlv")]
+pub mod rtlv;
+
+#[cfg(feature = "asva")]
+pub mod asva;
+
+#[cfg(feature = "rpkg")]
+pub mod rpkg;
+
 #[cfg(feature = "rune")]
 pub fn rune_install(ctx: &mut rune::Context) -> Result<(), rune::ContextError> {
 	#[cfg(feature = "material")]
@@ -30,5 +65,20 @@ pub fn rune_install(ctx: &mut rune::Context) -> Result<(), rune::ContextError> {
 	#[cfg(feature = "texture")]
 	ctx.install(texture::rune_module()?)?;
 
+	#[cfg(feature = "locr")]
+	ctx.install(locr::rune_module()?)?;
+
+	#[cfg(feature = "ditl")]
+	ctx.install(ditl::rune_module()?)?;
+
+	#[cfg(feature = "clng")]
+	ctx.install(clng::rune_module()?)?;
+
+	#[cfg(feature = "rtlv")]
+	ctx.install(rtlv::rune_module()?)?;
+
+	#[cfg(feature = "asva")]
+	ctx.install(asva::rune_module()?)?;
+
 	Ok(())
 }
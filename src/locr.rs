@@ -0,0 +1,383 @@
+use std::io::{Cursor, Read, Seek, SeekFrom};
+
+use glacier_commons::{
+	game::GlacierGame,
+	metadata::{ResourceMetadata, RuntimeID}
+};
+use indexmap::IndexMap;
+use thiserror::Error;
+use tryvial::try_fn;
+
+#[cfg(feature = "rune")]
+pub fn rune_module() -> Result<rune::Module, rune::ContextError> {
+	let mut module = rune::Module::with_crate_item("glacier_formats", ["locr"])?;
+
+	module.ty::<LocrError>()?;
+	module.ty::<LocalisedStrings>()?;
+	module.ty::<LocalisedStringMap>()?;
+	module.ty::<LanguageMap>()?;
+	module.function_meta(r_languages)?;
+
+	Ok(module)
+}
+
+type Result<T, E = LocrError> = std::result::Result<T, E>;
+
+#[derive(Error, Debug)]
+#[cfg_attr(feature = "rune", derive(better_rune_derive::Any))]
+#[cfg_attr(feature = "rune", rune(item = ::glacier_formats::locr))]
+#[cfg_attr(feature = "rune", rune_derive(DISPLAY_FMT, DEBUG_FMT))]
+pub enum LocrError {
+	#[error("seek error: {0}")]
+	Seek(#[from] std::io::Error),
+
+	#[error("invalid number: {0}")]
+	InvalidNumber(#[from] std::num::TryFromIntError),
+
+	#[error("invalid utf-8: {0}")]
+	InvalidString(#[from] std::string::FromUtf8Error),
+
+	#[error("language {0} is not supported by this game")]
+	UnknownLanguage(String),
+
+	#[error("string {1:#010x} appears more than once in language {0}")]
+	DuplicateHash(String, u32)
+}
+
+/// The languages of a game, in the order of their sections in a LOCR.
+pub fn languages(version: GlacierGame) -> &'static [&'static str] {
+	match version {
+		GlacierGame::H1 => &["xx", "en", "fr", "it", "de", "es", "ru", "mx", "br", "pl", "cn", "jp"],
+		GlacierGame::H2 => &[
+			"xx", "en", "fr", "it", "de", "es", "ru", "mx", "br", "pl", "cn", "jp", "tc"
+		],
+		GlacierGame::H3 | GlacierGame::FL => &["xx", "en", "fr", "it", "de", "es", "ru", "cn", "tc", "jp"]
+	}
+}
+
+#[cfg(feature = "rune")]
+#[rune::function(path = languages)]
+fn r_languages(version: GlacierGame) -> Vec<String> {
+	languages(version).iter().map(|x| x.to_string()).collect()
+}
+
+/// Decipher a string from a LOCR.
+pub fn decipher(data: &[u8]) -> Vec<u8> {
+	data.iter()
+		.map(|&x| {
+			let x = x ^ 0xE2;
+
+			(x & 0x81)
+				| ((x & 0x02) << 1)
+				| ((x & 0x04) << 2)
+				| ((x & 0x08) << 3)
+				| ((x & 0x10) >> 3)
+				| ((x & 0x20) >> 2)
+				| ((x & 0x40) >> 1)
+		})
+		.collect()
+}
+
+/// Cipher a string for a LOCR; the inverse of [`decipher`].
+pub fn cipher(data: &[u8]) -> Vec<u8> {
+	data.iter()
+		.map(|&x| {
+			((x & 0x81)
+				| ((x & 0x04) >> 1)
+				| ((x & 0x10) >> 2)
+				| ((x & 0x40) >> 3)
+				| ((x & 0x02) << 3)
+				| ((x & 0x08) << 2)
+				| ((x & 0x20) << 1))
+				^ 0xE2
+		})
+		.collect()
+}
+
+/// A localised string table; a parsed LOCR file.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "rune", derive(better_rune_derive::Any))]
+#[cfg_attr(feature = "rune", rune(item = ::glacier_formats::locr))]
+#[cfg_attr(feature = "rune", rune_derive(DEBUG_FMT, PARTIAL_EQ, EQ, CLONE))]
+#[cfg_attr(feature = "rune", rune_functions(Self::parse__meta, Self::generate__meta))]
+#[cfg_attr(feature = "rune", rune(install_with = Self::rune_install))]
+#[cfg_attr(feature = "rune", rune(constructor_fn = Self::rune_construct))]
+pub struct LocalisedStrings {
+	#[cfg_attr(feature = "rune", rune(get, set))]
+	pub id: RuntimeID,
+
+	/// The byte before the section offsets in versions after H1. Written as 0 if not set.
+	#[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+	#[cfg_attr(feature = "rune", rune(get, set))]
+	pub header_byte: Option<u8>,
+
+	/// The strings of each language, keyed by the CRC32 of the string's name. Languages without a section are omitted.
+	pub languages: IndexMap<String, IndexMap<u32, String>>
+}
+
+#[cfg(feature = "rune")]
+crate::rune_map::ordered_map!(
+	/// The strings of a LOCR language by hash, in order.
+	LocalisedStringMap,
+	u32,
+	String,
+	::glacier_formats::locr
+);
+
+#[cfg(feature = "rune")]
+crate::rune_map::ordered_map!(
+	/// The languages of a LOCR and their strings, in order.
+	LanguageMap,
+	String,
+	LocalisedStringMap,
+	::glacier_formats::locr
+);
+
+#[cfg(feature = "rune")]
+impl LocalisedStrings {
+	fn rune_construct(id: RuntimeID) -> Self {
+		Self {
+			id,
+			header_byte: None,
+			languages: IndexMap::new()
+		}
+	}
+
+	fn rune_install(module: &mut rune::Module) -> Result<(), rune::ContextError> {
+		module.field_function(&rune::runtime::Protocol::GET, "languages", |s: &Self| {
+			LanguageMap(
+				s.languages
+					.iter()
+					.map(|(language, strings)| (language.to_owned(), LocalisedStringMap(strings.to_owned())))
+					.collect()
+			)
+		})?;
+
+		module.field_function(
+			&rune::runtime::Protocol::SET,
+			"languages",
+			|s: &mut Self, languages: LanguageMap| {
+				s.languages = languages
+					.0
+					.into_iter()
+					.map(|(language, strings)| (language, strings.0))
+					.collect();
+			}
+		)?;
+
+		Ok(())
+	}
+}
+
+impl LocalisedStrings {
+	/// Parse a LOCR. Strings are keyed by hash, so a section with a repeated hash is rejected rather than losing entries.
+	#[try_fn]
+	#[cfg_attr(feature = "rune", rune::function(keep, path = Self::parse))]
+	#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+	pub fn parse(version: GlacierGame, locr_data: &[u8], locr_metadata: &ResourceMetadata) -> Result<Self> {
+		let mut locr = Cursor::new(locr_data);
+
+		// Versions after H1 have an extra byte before the section offsets
+		let header_byte = if version != GlacierGame::H1 {
+			let mut x = [0u8; 1];
+			locr.read_exact(&mut x)?;
+			Some(x[0])
+		} else {
+			None
+		};
+
+		let mut offsets = vec![];
+		for _ in languages(version) {
+			offsets.push(u32::from_le_bytes({
+				let mut x = [0u8; 4];
+				locr.read_exact(&mut x)?;
+				x
+			}));
+		}
+
+		let mut sections = IndexMap::new();
+
+		for (&language, offset) in languages(version).iter().zip(offsets) {
+			if offset == u32::MAX {
+				continue;
+			}
+
+			locr.seek(SeekFrom::Start(offset.into()))?;
+
+			let count = u32::from_le_bytes({
+				let mut x = [0u8; 4];
+				locr.read_exact(&mut x)?;
+				x
+			});
+
+			let mut strings = IndexMap::with_capacity(usize::try_from(count)?);
+
+			for _ in 0..count {
+				let hash = u32::from_le_bytes({
+					let mut x = [0u8; 4];
+					locr.read_exact(&mut x)?;
+					x
+				});
+
+				let length = u32::from_le_bytes({
+					let mut x = [0u8; 4];
+					locr.read_exact(&mut x)?;
+					x
+				});
+
+				let mut string_data = vec![0; usize::try_from(length)?];
+				locr.read_exact(&mut string_data)?;
+
+				// Null terminator
+				locr.seek(SeekFrom::Current(1))?;
+
+				if strings
+					.insert(hash, String::from_utf8(decipher(&string_data))?)
+					.is_some()
+				{
+					return Err(LocrError::DuplicateHash(language.to_owned(), hash));
+				}
+			}
+
+			sections.insert(language.to_owned(), strings);
+		}
+
+		Self {
+			id: locr_metadata.id,
+			header_byte,
+			languages: sections
+		}
+	}
+
+	/// Serialise this LOCR. Languages not present are written without a section.
+	#[try_fn]
+	#[cfg_attr(feature = "rune", rune::function(keep, instance))]
+	#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+	pub fn generate(self, version: GlacierGame) -> Result<(Vec<u8>, ResourceMetadata)> {
+		if let Some(language) = self
+			.languages
+			.keys()
+			.find(|x| !languages(version).contains(&x.as_str()))
+		{
+			return Err(LocrError::UnknownLanguage(language.to_owned()));
+		}
+
+		let mut locr = vec![];
+
+		if version != GlacierGame::H1 {
+			locr.push(self.header_byte.unwrap_or(0));
+		}
+
+		let offsets_start = locr.len();
+		locr.resize(offsets_start + languages(version).len() * 4, 0);
+
+		for (i, language) in languages(version).iter().enumerate() {
+			let offset = if let Some(strings) = self.languages.get(*language) {
+				let offset = u32::try_from(locr.len())?;
+
+				locr.extend_from_slice(&u32::try_from(strings.len())?.to_le_bytes());
+
+				for (hash, string) in strings {
+					locr.extend_from_slice(&hash.to_le_bytes());
+					locr.extend_from_slice(&u32::try_from(string.len())?.to_le_bytes());
+					locr.extend_from_slice(&cipher(string.as_bytes()));
+					locr.push(0);
+				}
+
+				offset
+			} else {
+				u32::MAX
+			};
+
+			locr[offsets_start + i * 4..offsets_start + i * 4 + 4].copy_from_slice(&offset.to_le_bytes());
+		}
+
+		(
+			locr,
+			ResourceMetadata {
+				id: self.id,
+				resource_type: "LOCR".try_into().unwrap(),
+				compressed: ResourceMetadata::infer_compressed("LOCR".try_into().unwrap()),
+				scrambled: ResourceMetadata::infer_scrambled("LOCR".try_into().unwrap()),
+				references: vec![]
+			}
+		)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn round_trip() {
+		let locr = LocalisedStrings {
+			id: "00D3A2D3E2A6E3F4".parse().unwrap(),
+			header_byte: Some(1),
+			languages: IndexMap::from([
+				(
+					"en".into(),
+					IndexMap::from([(0x8C3E_1F2A, "Agent 47".into()), (0x0000_0001, "Diana".into())])
+				),
+				("fr".into(), IndexMap::from([(0x8C3E_1F2A, "Agent 47".into())])),
+				("jp".into(), IndexMap::new())
+			])
+		};
+
+		let (data, metadata) = locr.clone().generate(GlacierGame::H3).unwrap();
+		let parsed = LocalisedStrings::parse(GlacierGame::H3, &data, &metadata).unwrap();
+
+		assert_eq!(data[0], 1);
+		assert_eq!(parsed, locr);
+		assert_eq!(parsed.generate(GlacierGame::H3).unwrap().0, data);
+	}
+
+	#[test]
+	fn h1_round_trip() {
+		let locr = LocalisedStrings {
+			id: "00D3A2D3E2A6E3F4".parse().unwrap(),
+			header_byte: None,
+			languages: IndexMap::from([
+				("en".into(), IndexMap::from([(0x8C3E_1F2A, "Agent 47".into())])),
+				("jp".into(), IndexMap::from([(0x8C3E_1F2A, "エージェント47".into())]))
+			])
+		};
+
+		let (data, metadata) = locr.clone().generate(GlacierGame::H1).unwrap();
+		let parsed = LocalisedStrings::parse(GlacierGame::H1, &data, &metadata).unwrap();
+
+		// No header byte; the offsets of all 12 sections come first, with the first section following them
+		assert_eq!(data[0..4], u32::MAX.to_le_bytes());
+		assert_eq!(data[4..8], 48u32.to_le_bytes());
+
+		assert_eq!(parsed, locr);
+		assert_eq!(parsed.generate(GlacierGame::H1).unwrap().0, data);
+	}
+
+	#[test]
+	fn duplicate_hash() {
+		let (mut data, metadata) = LocalisedStrings {
+			id: "00D3A2D3E2A6E3F4".parse().unwrap(),
+			header_byte: None,
+			languages: IndexMap::from([("en".into(), IndexMap::from([(1, "a".into()), (2, "b".into())]))])
+		}
+		.generate(GlacierGame::H1)
+		.unwrap();
+
+		// Change the second hash to match the first
+		data[62..66].copy_from_slice(&1u32.to_le_bytes());
+
+		assert!(matches!(
+			LocalisedStrings::parse(GlacierGame::H1, &data, &metadata),
+			Err(LocrError::DuplicateHash(language, 1)) if language == "en"
+		));
+	}
+
+	#[test]
+	fn cipher_round_trip() {
+		let data = (0..=u8::MAX).collect::<Vec<_>>();
+
+		assert_eq!(decipher(&cipher(&data)), data);
+	}
+}
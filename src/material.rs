@@ -1,5 +1,4 @@
 use std::{
-	collections::HashMap,
 	fmt::Display,
 	io::{Cursor, Read, Seek, SeekFrom},
 	num::ParseIntError,
@@ -18,7 +17,11 @@ pub fn rune_module() -> Result<rune::Module, rune::ContextError> {
 	module.ty::<MaterialError>()?;
 	module.ty::<MaterialEntity>()?;
 	module.ty::<MaterialOverride>()?;
+	module.ty::<MaterialOverrideMap>()?;
+	module.ty::<Colour>()?;
 	module.ty::<IntermediateMaterialProperty>()?;
+	module.ty::<UnknownMaterialProperty>()?;
+	module.ty::<UnknownPropertyData>()?;
 	module.ty::<FloatVal>()?;
 	module.ty::<MaterialInstance>()?;
 	module.ty::<MaterialType>()?;
@@ -29,6 +32,13 @@ pub fn rune_module() -> Result<rune::Module, rune::ContextError> {
 	module.ty::<CullingMode>()?;
 	module.ty::<BlendMode>()?;
 	module.ty::<MaterialPropertyValue>()?;
+	module.ty::<MaterialPropertyMap>()?;
+	module.ty::<UnknownSubPropertyMap>()?;
+	module.ty::<MaterialPropertyType>()?;
+	module.ty::<TilingMode>()?;
+	module.ty::<TextureSlotType>()?;
+	module.ty::<MaterialClass>()?;
+	module.ty::<MaterialValidationIssue>()?;
 
 	Ok(module)
 }
@@ -76,9 +86,6 @@ pub enum MaterialError {
 	#[error("float vector of correct size for colour expected for property {0}")]
 	InvalidColor(String),
 
-	#[error("invalid material type: {0}")]
-	InvalidMaterialType(String),
-
 	#[error("invalid culling mode: {0}")]
 	InvalidCullingMode(String),
 
@@ -89,7 +96,13 @@ pub enum MaterialError {
 	InvalidVector,
 
 	#[error("invalid hex: {0}")]
-	InvalidHex(#[from] ParseIntError)
+	InvalidHex(#[from] ParseIntError),
+
+	#[error("invalid colour: {0}")]
+	InvalidColour(String),
+
+	#[error("colours must have 3 or 4 components")]
+	InvalidColourComponents
 }
 
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -97,7 +110,10 @@ pub enum MaterialError {
 #[cfg_attr(feature = "rune", derive(better_rune_derive::Any))]
 #[cfg_attr(feature = "rune", rune(item = ::glacier_formats::material))]
 #[cfg_attr(feature = "rune", rune_derive(DEBUG_FMT, PARTIAL_EQ, CLONE))]
-#[cfg_attr(feature = "rune", rune_functions(Self::parse__meta, Self::generate__meta))]
+#[cfg_attr(
+	feature = "rune",
+	rune_functions(Self::parse__meta, Self::generate__meta, Self::validate__meta)
+)]
 #[cfg_attr(feature = "rune", rune(install_with = Self::rune_install))]
 #[cfg_attr(feature = "rune", rune(constructor_fn = Self::rune_construct))]
 pub struct MaterialEntity {
@@ -113,32 +129,41 @@ pub struct MaterialEntity {
 	pub overrides: IndexMap<String, MaterialOverride>
 }
 
+#[cfg(feature = "rune")]
+crate::rune_map::ordered_map!(
+	/// The overrides of a material entity, in order.
+	MaterialOverrideMap,
+	String,
+	MaterialOverride,
+	::glacier_formats::material
+);
+
 #[cfg(feature = "rune")]
 impl MaterialEntity {
 	fn rune_construct(
 		factory: RuntimeID,
 		blueprint: RuntimeID,
 		material: RuntimeID,
-		overrides: HashMap<String, MaterialOverride>
+		overrides: MaterialOverrideMap
 	) -> Self {
 		Self {
 			factory,
 			blueprint,
 			material,
-			overrides: overrides.into_iter().collect()
+			overrides: overrides.0
 		}
 	}
 
 	fn rune_install(module: &mut rune::Module) -> Result<(), rune::ContextError> {
 		module.field_function(&rune::runtime::Protocol::GET, "overrides", |s: &Self| {
-			s.overrides.clone().into_iter().collect::<HashMap<_, _>>()
+			MaterialOverrideMap(s.overrides.clone())
 		})?;
 
 		module.field_function(
 			&rune::runtime::Protocol::SET,
 			"overrides",
-			|s: &mut Self, value: HashMap<String, MaterialOverride>| {
-				s.overrides = value.into_iter().collect();
+			|s: &mut Self, value: MaterialOverrideMap| {
+				s.overrides = value.0;
 			}
 		)?;
 
@@ -158,7 +183,7 @@ pub enum MaterialOverride {
 	Texture(#[cfg_attr(feature = "rune", rune(get, set))] Option<RuntimeID>),
 
 	#[cfg_attr(feature = "rune", rune(constructor))]
-	Color(#[cfg_attr(feature = "rune", rune(get, set))] String),
+	Color(#[cfg_attr(feature = "rune", rune(get, set))] Colour),
 
 	#[cfg_attr(feature = "rune", rune(constructor))]
 	Float(#[cfg_attr(feature = "rune", rune(get, set))] f32),
@@ -167,6 +192,170 @@ pub enum MaterialOverride {
 	Vector(#[cfg_attr(feature = "rune", rune(get, set))] Vec<f32>)
 }
 
+impl MaterialOverride {
+	/// Get the type of the property this override sets.
+	pub fn property_type(&self) -> MaterialPropertyType {
+		match self {
+			Self::Texture(_) => MaterialPropertyType::Texture,
+			Self::Color(value) => {
+				if value.a.is_some() {
+					MaterialPropertyType::Colour4
+				} else {
+					MaterialPropertyType::Colour
+				}
+			}
+			Self::Float(_) => MaterialPropertyType::Float,
+			Self::Vector(value) => MaterialPropertyType::Vector(value.len())
+		}
+	}
+}
+
+/// An RGB or RGBA colour, stored losslessly as the floats used by the game.
+///
+/// Colours are serialised as hex strings where every component is exactly representable as an 8-bit value and as
+/// float arrays otherwise (e.g. for HDR or emissive colours).
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "SerialisedColour", into = "SerialisedColour"))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "rune", serde_with::apply(_ => #[rune(get, set)]))]
+#[cfg_attr(feature = "rune", derive(better_rune_derive::Any))]
+#[cfg_attr(feature = "rune", rune(item = ::glacier_formats::material))]
+#[cfg_attr(feature = "rune", rune_derive(DEBUG_FMT, PARTIAL_EQ, CLONE))]
+#[cfg_attr(feature = "rune", rune(constructor))]
+#[cfg_attr(feature = "rune", rune_functions(Self::to_hex__meta, Self::components__meta))]
+pub struct Colour {
+	pub r: f32,
+	pub g: f32,
+	pub b: f32,
+
+	/// Only present for RGBA colours.
+	pub a: Option<f32>
+}
+
+impl Colour {
+	/// Construct a colour from 3 (RGB) or 4 (RGBA) components.
+	#[try_fn]
+	pub fn from_components(components: &[f32]) -> Result<Self> {
+		match *components {
+			[r, g, b] => Self { r, g, b, a: None },
+			[r, g, b, a] => Self { r, g, b, a: Some(a) },
+			_ => return Err(MaterialError::InvalidColourComponents)
+		}
+	}
+
+	/// The components of this colour, in RGB(A) order.
+	#[cfg_attr(feature = "rune", rune::function(keep, instance))]
+	pub fn components(&self) -> Vec<f32> {
+		[self.r, self.g, self.b].into_iter().chain(self.a).collect()
+	}
+
+	/// Get the hex representation of this colour, if every component can be represented exactly by an 8-bit value.
+	#[cfg_attr(feature = "rune", rune::function(keep, instance))]
+	pub fn to_hex(&self) -> Option<String> {
+		self.components()
+			.into_iter()
+			.map(|x| {
+				let byte = (x * 255.0).round();
+
+				((0.0..=255.0).contains(&byte) && (byte as u8 as f32 / 255.0).to_bits() == x.to_bits())
+					.then_some(byte as u8)
+			})
+			.try_fold(String::from("#"), |acc, x| Some(acc + &format!("{:0>2x}", x?)))
+	}
+}
+
+impl FromStr for Colour {
+	type Err = MaterialError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let hex = s.strip_prefix('#').unwrap_or(s);
+
+		if !hex.is_ascii() || (hex.len() != 6 && hex.len() != 8) {
+			return Err(MaterialError::InvalidColour(s.into()));
+		}
+
+		Self::from_components(
+			&(0..hex.len() / 2)
+				.map(|i| u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).map(|x| x as f32 / 255.0))
+				.collect::<Result<Vec<_>, _>>()?
+		)
+	}
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+enum SerialisedColour {
+	Hex(String),
+	Components(Vec<f32>)
+}
+
+#[cfg(feature = "serde")]
+impl From<Colour> for SerialisedColour {
+	fn from(value: Colour) -> Self {
+		match value.to_hex() {
+			Some(hex) => Self::Hex(hex),
+			None => Self::Components(value.components())
+		}
+	}
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<SerialisedColour> for Colour {
+	type Error = MaterialError;
+
+	fn try_from(value: SerialisedColour) -> Result<Self, Self::Error> {
+		match value {
+			SerialisedColour::Hex(hex) => hex.parse(),
+			SerialisedColour::Components(components) => Self::from_components(&components)
+		}
+	}
+}
+
+/// Colour properties are untagged, so the float form is written as an object of components under `value` to avoid being
+/// read as a vector property.
+#[cfg(feature = "serde")]
+mod colour_property {
+	use serde::{Deserialize, Serialize};
+
+	use super::Colour;
+
+	#[derive(Serialize, Deserialize)]
+	#[serde(untagged)]
+	enum ColourProperty {
+		Hex(String),
+
+		Components {
+			r: f32,
+			g: f32,
+			b: f32,
+
+			#[serde(default, skip_serializing_if = "Option::is_none")]
+			a: Option<f32>
+		}
+	}
+
+	pub fn serialize<S: serde::Serializer>(value: &Colour, serializer: S) -> Result<S::Ok, S::Error> {
+		match value.to_hex() {
+			Some(hex) => ColourProperty::Hex(hex),
+			None => ColourProperty::Components {
+				r: value.r,
+				g: value.g,
+				b: value.b,
+				a: value.a
+			}
+		}
+		.serialize(serializer)
+	}
+
+	pub fn deserialize<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Colour, D::Error> {
+		match ColourProperty::deserialize(deserializer)? {
+			ColourProperty::Hex(hex) => hex.parse().map_err(serde::de::Error::custom),
+			ColourProperty::Components { r, g, b, a } => Ok(Colour { r, g, b, a })
+		}
+	}
+}
+
 impl MaterialEntity {
 	/// Parse a material entity (MATT/MATB).
 	#[try_fn]
@@ -268,12 +457,12 @@ impl MaterialEntity {
 							x
 						});
 
-						MaterialOverride::Color(format!(
-							"#{:0>2x}{:0>2x}{:0>2x}",
-							(x * 255.0).round() as u8,
-							(y * 255.0).round() as u8,
-							(z * 255.0).round() as u8
-						))
+						MaterialOverride::Color(Colour {
+							r: x,
+							g: y,
+							b: z,
+							a: None
+						})
 					}
 
 					// An RGBA colour.
@@ -302,13 +491,12 @@ impl MaterialEntity {
 							x
 						});
 
-						MaterialOverride::Color(format!(
-							"#{:0>2x}{:0>2x}{:0>2x}{:0>2x}",
-							(x * 255.0).round() as u8,
-							(y * 255.0).round() as u8,
-							(z * 255.0).round() as u8,
-							(w * 255.0).round() as u8
-						))
+						MaterialOverride::Color(Colour {
+							r: x,
+							g: y,
+							b: z,
+							a: Some(w)
+						})
 					}
 
 					// A float.
@@ -465,42 +653,13 @@ impl MaterialEntity {
 				}
 
 				MaterialOverride::Color(value) => {
-					if value.len() > 7 {
-						let r =
-							u8::from_str_radix(&value.chars().skip(1).take(2).collect::<String>(), 16)? as f32 / 255.0;
-
-						let g =
-							u8::from_str_radix(&value.chars().skip(3).take(2).collect::<String>(), 16)? as f32 / 255.0;
-
-						let b =
-							u8::from_str_radix(&value.chars().skip(5).take(2).collect::<String>(), 16)? as f32 / 255.0;
-
-						let a =
-							u8::from_str_radix(&value.chars().skip(7).take(2).collect::<String>(), 16)? as f32 / 255.0;
-
-						matb.extend_from_slice(&[3]);
-						matt.extend_from_slice(&[3]);
-
-						matt.extend_from_slice(&r.to_le_bytes());
-						matt.extend_from_slice(&g.to_le_bytes());
-						matt.extend_from_slice(&b.to_le_bytes());
-						matt.extend_from_slice(&a.to_le_bytes());
-					} else {
-						let r =
-							u8::from_str_radix(&value.chars().skip(1).take(2).collect::<String>(), 16)? as f32 / 255.0;
-
-						let g =
-							u8::from_str_radix(&value.chars().skip(3).take(2).collect::<String>(), 16)? as f32 / 255.0;
-
-						let b =
-							u8::from_str_radix(&value.chars().skip(5).take(2).collect::<String>(), 16)? as f32 / 255.0;
+					let entry_type = if value.a.is_some() { 3 } else { 2 };
 
-						matb.extend_from_slice(&[2]);
-						matt.extend_from_slice(&[2]);
+					matb.extend_from_slice(&[entry_type]);
+					matt.extend_from_slice(&[entry_type]);
 
-						matt.extend_from_slice(&r.to_le_bytes());
-						matt.extend_from_slice(&g.to_le_bytes());
-						matt.extend_from_slice(&b.to_le_bytes());
+					for component in value.components() {
+						matt.extend_from_slice(&component.to_le_bytes());
 					}
 				}
 
@@ -560,6 +719,8 @@ impl MaterialEntity {
 	}
 }
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value"))]
 #[cfg_attr(feature = "rune", derive(better_rune_derive::Any))]
 #[cfg_attr(feature = "rune", rune(item = ::glacier_formats::material))]
 #[cfg_attr(feature = "rune", rune_derive(DEBUG_FMT, PARTIAL_EQ))]
@@ -662,9 +823,60 @@ pub enum IntermediateMaterialProperty {
 	ZBias(#[cfg_attr(feature = "rune", rune(get, set))] u32),
 
 	#[cfg_attr(feature = "rune", rune(constructor))]
-	ZOffset(#[cfg_attr(feature = "rune", rune(get, set))] f32)
+	ZOffset(#[cfg_attr(feature = "rune", rune(get, set))] f32),
+
+	/// A property with a tag or type not known to this library, kept as-is.
+	#[cfg_attr(feature = "rune", rune(constructor))]
+	Unknown(#[cfg_attr(feature = "rune", rune(get, set))] UnknownMaterialProperty)
+}
+
+/// A property with a tag or type not known to this library (e.g. from a newer game).
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "rune", serde_with::apply(_ => #[rune(get, set)]))]
+#[cfg_attr(feature = "rune", derive(better_rune_derive::Any))]
+#[cfg_attr(feature = "rune", rune(item = ::glacier_formats::material))]
+#[cfg_attr(feature = "rune", rune_derive(DEBUG_FMT, PARTIAL_EQ, CLONE))]
+#[cfg_attr(feature = "rune", rune(constructor))]
+pub struct UnknownMaterialProperty {
+	/// The four-character tag of the property.
+	pub tag: String,
+
+	pub data: UnknownPropertyData,
+
+	/// The position of the property within its parent, so that it can be written back in place. Properties without a
+	/// position are written after the known properties of their parent.
+	#[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+	pub position: Option<usize>
+}
+
+/// The raw data of an unknown property, according to its type.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value"))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "rune", derive(better_rune_derive::Any))]
+#[cfg_attr(feature = "rune", rune(item = ::glacier_formats::material))]
+#[cfg_attr(feature = "rune", rune_derive(DEBUG_FMT, PARTIAL_EQ, CLONE))]
+pub enum UnknownPropertyData {
+	#[cfg_attr(feature = "rune", rune(constructor))]
+	Float(#[cfg_attr(feature = "rune", rune(get, set))] f32),
+
+	#[cfg_attr(feature = "rune", rune(constructor))]
+	Vector(#[cfg_attr(feature = "rune", rune(get, set))] Vec<f32>),
+
+	#[cfg_attr(feature = "rune", rune(constructor))]
+	String(#[cfg_attr(feature = "rune", rune(get, set))] String),
+
+	#[cfg_attr(feature = "rune", rune(constructor))]
+	Int(#[cfg_attr(feature = "rune", rune(get, set))] u32),
+
+	#[cfg_attr(feature = "rune", rune(constructor))]
+	Properties(#[cfg_attr(feature = "rune", rune(get, set))] Vec<IntermediateMaterialProperty>)
 }
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(untagged))]
 #[derive(Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "rune", derive(better_rune_derive::Any))]
 #[cfg_attr(feature = "rune", rune(item = ::glacier_formats::material))]
@@ -684,6 +896,10 @@ pub enum FloatVal {
 #[cfg_attr(feature = "rune", derive(better_rune_derive::Any))]
 #[cfg_attr(feature = "rune", rune(item = ::glacier_formats::material))]
 #[cfg_attr(feature = "rune", rune_derive(DEBUG_FMT, PARTIAL_EQ, CLONE))]
+#[cfg_attr(
+	feature = "rune",
+	rune_functions(Self::parse__meta, Self::generate__meta, Self::validate__meta, Self::resolve__meta)
+)]
 pub struct MaterialInstance {
 	pub id: RuntimeID,
 
@@ -696,20 +912,44 @@ pub struct MaterialInstance {
 	#[cfg_attr(feature = "serde", serde(default))]
 	pub tags: String,
 
-	pub class: Option<RuntimeID>,
-	pub descriptor: Option<RuntimeID>,
+	/// The material class (MATE) of this instance, along with its reference flags.
+	pub class: Option<ResourceReference>,
+
+	/// The material descriptor (ERES) of this instance, along with its reference flags.
+	pub descriptor: Option<ResourceReference>,
+
+	/// The impact material (lImpactMaterial) referenced by this instance, along with its reference flags.
+	#[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+	pub impact_material: Option<ResourceReference>,
+
+	/// The effect resource (lEffectResource) referenced by this instance, along with its reference flags.
+	#[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+	pub effect_resource: Option<ResourceReference>,
+
 	pub class_flags: ClassFlags,
 	pub instance_flags: InstanceFlags,
 
+	/// The remaining header words following the instance offset, if they differ from the usual 3 followed by three
+	/// zeroes ([`DEFAULT_HEADER_WORDS`]).
+	#[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+	pub extra_header_words: Option<Vec<u32>>,
+
+	/// Properties of the instance (besides its name, tags and binder) not known to this library.
+	#[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Vec::is_empty"))]
+	pub unknown_instance_properties: Vec<UnknownMaterialProperty>,
+
 	#[cfg_attr(feature = "serde", serde(flatten))]
 	pub binder: Binder
 }
 
+/// The header words which follow the instance offset in almost every MATI.
+pub const DEFAULT_HEADER_WORDS: [u32; 4] = [3, 0, 0, 0];
+
 #[cfg_attr(
 	feature = "serde",
 	derive(serde_with::SerializeDisplay, serde_with::DeserializeFromStr)
 )]
-#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
 #[cfg_attr(feature = "rune", derive(better_rune_derive::Any))]
 #[cfg_attr(feature = "rune", rune(item = ::glacier_formats::material))]
 #[cfg_attr(feature = "rune", rune_derive(DEBUG_FMT, PARTIAL_EQ, EQ, CLONE))]
@@ -727,7 +967,11 @@ pub enum MaterialType {
 	SpriteParticleAO,
 
 	#[cfg_attr(feature = "rune", rune(constructor))]
-	SpriteParticleVelocity
+	SpriteParticleVelocity,
+
+	/// A material type not known to this library (e.g. from a newer game), kept as-is.
+	#[cfg_attr(feature = "rune", rune(constructor))]
+	Other(#[cfg_attr(feature = "rune", rune(get, set))] String)
 }
 
 impl FromStr for MaterialType {
@@ -740,7 +984,7 @@ impl FromStr for MaterialType {
 			"StandardWeighted" => Ok(Self::StandardWeighted),
 			"SpriteParticle_AO" => Ok(Self::SpriteParticleAO),
 			"SpriteParticleVelocity" => Ok(Self::SpriteParticleVelocity),
-			_ => Err(MaterialError::InvalidMaterialType(s.into()))
+			_ => Ok(Self::Other(s.into()))
 		}
 	}
 }
@@ -752,7 +996,8 @@ impl Display for MaterialType {
 			Self::StandardLinked => write!(f, "StandardLinked"),
 			Self::StandardWeighted => write!(f, "StandardWeighted"),
 			Self::SpriteParticleAO => write!(f, "SpriteParticle_AO"),
-			Self::SpriteParticleVelocity => write!(f, "SpriteParticleVelocity")
+			Self::SpriteParticleVelocity => write!(f, "SpriteParticleVelocity"),
+			Self::Other(other) => write!(f, "{other}")
 		}
 	}
 }
@@ -763,6 +1008,7 @@ impl Display for MaterialType {
 #[cfg_attr(feature = "rune", derive(better_rune_derive::Any))]
 #[cfg_attr(feature = "rune", rune(item = ::glacier_formats::material))]
 #[cfg_attr(feature = "rune", rune_derive(DEBUG_FMT, PARTIAL_EQ, EQ, CLONE))]
+#[cfg_attr(feature = "rune", rune_functions(Self::from_u32__meta, Self::as_u32__meta))]
 pub struct ClassFlags {
 	#[cfg_attr(feature = "serde", serde(rename = "reflection2D"))]
 	#[cfg_attr(feature = "serde", serde(default))]
@@ -869,6 +1115,7 @@ pub struct ClassFlags {
 }
 
 impl ClassFlags {
+	#[cfg_attr(feature = "rune", rune::function(keep, path = Self::from_u32))]
 	pub fn from_u32(flags: u32) -> Self {
 		Self {
 			reflection_2d: flags & 0x1 == 0x1,
@@ -896,6 +1143,7 @@ impl ClassFlags {
 		}
 	}
 
+	#[cfg_attr(feature = "rune", rune::function(keep, instance))]
 	pub fn as_u32(&self) -> u32 {
 		let mut flags = 0;
 
@@ -998,6 +1246,7 @@ impl ClassFlags {
 #[cfg_attr(feature = "rune", derive(better_rune_derive::Any))]
 #[cfg_attr(feature = "rune", rune(item = ::glacier_formats::material))]
 #[cfg_attr(feature = "rune", rune_derive(DEBUG_FMT, PARTIAL_EQ, EQ, CLONE))]
+#[cfg_attr(feature = "rune", rune_functions(Self::from_u32__meta, Self::as_u32__meta))]
 pub struct InstanceFlags {
 	#[cfg_attr(feature = "serde", serde(default))]
 	#[cfg_attr(feature = "serde", serde(skip_serializing_if = "std::ops::Not::not"))]
@@ -1065,6 +1314,7 @@ pub struct InstanceFlags {
 }
 
 impl InstanceFlags {
+	#[cfg_attr(feature = "rune", rune::function(keep, path = Self::from_u32))]
 	pub fn from_u32(flags: u32) -> Self {
 		Self {
 			opaque_emissive: flags & 0x1 == 0x1,
@@ -1086,6 +1336,7 @@ impl InstanceFlags {
 		}
 	}
 
+	#[cfg_attr(feature = "rune", rune::function(keep, instance))]
 	pub fn as_u32(&self) -> u32 {
 		let mut flags = 0;
 
@@ -1169,28 +1420,69 @@ pub struct Binder {
 	#[cfg_attr(feature = "rune", rune(get, set))]
 	pub render_state: RenderState,
 
-	pub properties: IndexMap<String, MaterialPropertyValue>
+	pub properties: IndexMap<String, MaterialPropertyValue>,
+
+	/// Properties of the binder not known to this library.
+	#[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Vec::is_empty"))]
+	#[cfg_attr(feature = "rune", rune(get, set))]
+	pub unknown_properties: Vec<UnknownMaterialProperty>,
+
+	/// Sub-properties not known to this library within each texture, colour or float property, by property name.
+	#[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "IndexMap::is_empty"))]
+	pub unknown_sub_properties: IndexMap<String, Vec<UnknownMaterialProperty>>
 }
 
+#[cfg(feature = "rune")]
+crate::rune_map::ordered_map!(
+	/// The properties of a material binder, in order.
+	MaterialPropertyMap,
+	String,
+	MaterialPropertyValue,
+	::glacier_formats::material
+);
+
+#[cfg(feature = "rune")]
+crate::rune_map::ordered_map!(
+	/// The unknown sub-properties of a material binder's properties, by property name.
+	UnknownSubPropertyMap,
+	String,
+	Vec<UnknownMaterialProperty>,
+	::glacier_formats::material
+);
+
 #[cfg(feature = "rune")]
 impl Binder {
-	fn rune_construct(render_state: RenderState, properties: HashMap<String, MaterialPropertyValue>) -> Self {
+	fn rune_construct(render_state: RenderState, properties: MaterialPropertyMap) -> Self {
 		Self {
 			render_state,
-			properties: properties.into_iter().collect()
+			properties: properties.0,
+			unknown_properties: vec![],
+			unknown_sub_properties: IndexMap::new()
 		}
 	}
 
 	fn rune_install(module: &mut rune::Module) -> Result<(), rune::ContextError> {
 		module.field_function(&rune::runtime::Protocol::GET, "properties", |s: &Self| {
-			s.properties.clone().into_iter().collect::<Vec<_>>()
+			MaterialPropertyMap(s.properties.clone())
 		})?;
 
 		module.field_function(
 			&rune::runtime::Protocol::SET,
 			"properties",
-			|s: &mut Self, properties: Vec<(String, MaterialPropertyValue)>| {
-				s.properties = properties.into_iter().collect();
+			|s: &mut Self, properties: MaterialPropertyMap| {
+				s.properties = properties.0;
+			}
+		)?;
+
+		module.field_function(&rune::runtime::Protocol::GET, "unknown_sub_properties", |s: &Self| {
+			UnknownSubPropertyMap(s.unknown_sub_properties.clone())
+		})?;
+
+		module.field_function(
+			&rune::runtime::Protocol::SET,
+			"unknown_sub_properties",
+			|s: &mut Self, unknown_sub_properties: UnknownSubPropertyMap| {
+				s.unknown_sub_properties = unknown_sub_properties.0;
 			}
 		)?;
 
@@ -1264,7 +1556,11 @@ pub struct RenderState {
 	pub subsurface_blue: Option<f32>,
 
 	#[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
-	pub subsurface_value: Option<f32>
+	pub subsurface_value: Option<f32>,
+
+	/// Properties of the render state not known to this library.
+	#[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Vec::is_empty"))]
+	pub unknown_properties: Vec<UnknownMaterialProperty>
 }
 
 fn is_default_renderstate(value: &Option<String>) -> bool {
@@ -1402,20 +1698,20 @@ pub enum MaterialPropertyValue {
 		value: Option<RuntimeID>,
 
 		#[cfg_attr(feature = "serde", serde(rename = "tilingU"))]
-		#[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty"))]
+		#[cfg_attr(feature = "serde", serde(skip_serializing_if = "is_default"))]
 		#[cfg_attr(feature = "serde", serde(default))]
 		#[cfg_attr(feature = "rune", rune(get, set))]
-		tiling_u: String,
+		tiling_u: TilingMode,
 
 		#[cfg_attr(feature = "serde", serde(rename = "tilingV"))]
-		#[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty"))]
+		#[cfg_attr(feature = "serde", serde(skip_serializing_if = "is_default"))]
 		#[cfg_attr(feature = "serde", serde(default))]
 		#[cfg_attr(feature = "rune", rune(get, set))]
-		tiling_v: String,
+		tiling_v: TilingMode,
 
 		#[cfg_attr(feature = "serde", serde(rename = "type"))]
 		#[cfg_attr(feature = "rune", rune(get, set))]
-		texture_type: String
+		texture_type: TextureSlotType
 	},
 
 	#[cfg_attr(feature = "rune", rune(constructor))]
@@ -1423,110 +1719,357 @@ pub enum MaterialPropertyValue {
 		#[cfg_attr(feature = "rune", rune(get, set))]
 		enabled: bool,
 
+		#[cfg_attr(feature = "serde", serde(with = "colour_property"))]
 		#[cfg_attr(feature = "rune", rune(get, set))]
-		value: String
+		value: Colour
 	}
 }
 
-impl MaterialInstance {
-	/// Parse a material instance (MATI).
-	#[try_fn]
-	pub fn parse(mati_data: &[u8], mati_metadata: &ResourceMetadata) -> Result<Self> {
-		let mut mati = Cursor::new(mati_data);
+#[cfg(feature = "serde")]
+fn is_default<T: Default + PartialEq>(t: &T) -> bool {
+	t == &T::default()
+}
 
-		let header_offset = u32::from_le_bytes({
-			let mut x = [0u8; 4];
-			mati.read_exact(&mut x)?;
-			x
-		});
+/// The tiling mode of a texture slot along one axis (TILU/TILV).
+#[cfg_attr(
+	feature = "serde",
+	derive(serde_with::SerializeDisplay, serde_with::DeserializeFromStr)
+)]
+#[derive(PartialEq, Eq, Hash, Clone, Debug, Default)]
+#[cfg_attr(feature = "rune", derive(better_rune_derive::Any))]
+#[cfg_attr(feature = "rune", rune(item = ::glacier_formats::material))]
+#[cfg_attr(feature = "rune", rune_derive(DISPLAY_FMT, DEBUG_FMT, PARTIAL_EQ, EQ, CLONE))]
+pub enum TilingMode {
+	/// No tiling mode given (an empty string).
+	#[default]
+	#[cfg_attr(feature = "rune", rune(constructor))]
+	Unset,
 
-		mati.seek(SeekFrom::Start(header_offset.into()))?;
+	#[cfg_attr(feature = "rune", rune(constructor))]
+	Tiled,
 
-		let type_offset = u32::from_le_bytes({
-			let mut x = [0u8; 4];
-			mati.read_exact(&mut x)?;
-			x
-		});
+	#[cfg_attr(feature = "rune", rune(constructor))]
+	Clamp,
 
-		let material_type = String::from_utf8(
-			mati_data
-				.iter()
-				.skip(type_offset as usize)
-				.take_while(|x| **x != 0)
-				.cloned()
-				.collect()
-		)?;
+	#[cfg_attr(feature = "rune", rune(constructor))]
+	Mirror,
 
-		let mate_index = u32::from_le_bytes({
-			let mut x = [0u8; 4];
-			mati.read_exact(&mut x)?;
-			x
-		});
+	/// A tiling mode not known to this library, kept as-is.
+	#[cfg_attr(feature = "rune", rune(constructor))]
+	Other(#[cfg_attr(feature = "rune", rune(get, set))] String)
+}
 
-		let class_flags = u32::from_le_bytes({
-			let mut x = [0u8; 4];
-			mati.read_exact(&mut x)?;
-			x
-		});
+impl FromStr for TilingMode {
+	type Err = MaterialError;
 
-		let instance_flags = u32::from_le_bytes({
-			let mut x = [0u8; 4];
-			mati.read_exact(&mut x)?;
-			x
-		});
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"" => Ok(Self::Unset),
+			"TILED" => Ok(Self::Tiled),
+			"CLAMP" => Ok(Self::Clamp),
+			"MIRROR" => Ok(Self::Mirror),
+			_ => Ok(Self::Other(s.into()))
+		}
+	}
+}
 
-		let eres_index = u32::from_le_bytes({
-			let mut x = [0u8; 4];
-			mati.read_exact(&mut x)?;
-			x
-		});
+impl Display for TilingMode {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Unset => Ok(()),
+			Self::Tiled => write!(f, "TILED"),
+			Self::Clamp => write!(f, "CLAMP"),
+			Self::Mirror => write!(f, "MIRROR"),
+			Self::Other(other) => write!(f, "{other}")
+		}
+	}
+}
 
-		// Skipped: lImpactMaterial, lEffectResource
-		let _ = {
-			let mut x = [0u8; 8];
-			mati.read_exact(&mut x)?;
-			x[0]
-		};
+/// The type of texture expected by a texture slot (TYPE).
+#[cfg_attr(
+	feature = "serde",
+	derive(serde_with::SerializeDisplay, serde_with::DeserializeFromStr)
+)]
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+#[cfg_attr(feature = "rune", derive(better_rune_derive::Any))]
+#[cfg_attr(feature = "rune", rune(item = ::glacier_formats::material))]
+#[cfg_attr(feature = "rune", rune_derive(DISPLAY_FMT, DEBUG_FMT, PARTIAL_EQ, EQ, CLONE))]
+pub enum TextureSlotType {
+	#[cfg_attr(feature = "rune", rune(constructor))]
+	Default,
 
-		let instance_offset = u32::from_le_bytes({
-			let mut x = [0u8; 4];
-			mati.read_exact(&mut x)?;
-			x
-		});
+	#[cfg_attr(feature = "rune", rune(constructor))]
+	Colour,
 
-		let (name, tags, binder) = parse_instance(parse_material_property(
-			mati_data,
-			&mati_metadata.references,
-			instance_offset.into()
-		)?)?;
+	#[cfg_attr(feature = "rune", rune(constructor))]
+	Normal,
 
-		Self {
-			id: mati_metadata.id.to_owned(),
-			name,
-			material_type: material_type.parse()?,
-			tags,
-			class: mati_metadata
-				.references
-				.get(mate_index as usize)
-				.map(|x| x.resource.to_owned()),
-			descriptor: mati_metadata
-				.references
-				.get(eres_index as usize)
-				.map(|x| x.resource.to_owned()),
-			class_flags: ClassFlags::from_u32(class_flags),
-			instance_flags: InstanceFlags::from_u32(instance_flags),
-			binder
-		}
-	}
+	#[cfg_attr(feature = "rune", rune(constructor))]
+	Height,
 
-	/// Generate the game binary for this material instance.
-	#[try_fn]
-	pub fn generate(self) -> Result<(Vec<u8>, ResourceMetadata)> {
-		let mut mati = vec![];
-		let mut mati_references = vec![];
+	#[cfg_attr(feature = "rune", rune(constructor))]
+	CompoundNormal,
 
-		// Header offset (placeholder)
-		mati.extend_from_slice(&0u32.to_le_bytes());
+	#[cfg_attr(feature = "rune", rune(constructor))]
+	Billboard,
+
+	#[cfg_attr(feature = "rune", rune(constructor))]
+	Cubemap,
+
+	#[cfg_attr(feature = "rune", rune(constructor))]
+	Emission,
+
+	#[cfg_attr(feature = "rune", rune(constructor))]
+	Projection,
+
+	/// A texture slot type not known to this library, kept as-is.
+	#[cfg_attr(feature = "rune", rune(constructor))]
+	Other(#[cfg_attr(feature = "rune", rune(get, set))] String)
+}
+
+impl FromStr for TextureSlotType {
+	type Err = MaterialError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"Default" => Ok(Self::Default),
+			"Color" => Ok(Self::Colour),
+			"Normal" => Ok(Self::Normal),
+			"Height" => Ok(Self::Height),
+			"CompoundNormal" => Ok(Self::CompoundNormal),
+			"Billboard" => Ok(Self::Billboard),
+			"Cubemap" => Ok(Self::Cubemap),
+			"Emission" => Ok(Self::Emission),
+			"Projection" => Ok(Self::Projection),
+			_ => Ok(Self::Other(s.into()))
+		}
+	}
+}
+
+impl Display for TextureSlotType {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Default => write!(f, "Default"),
+			Self::Colour => write!(f, "Color"),
+			Self::Normal => write!(f, "Normal"),
+			Self::Height => write!(f, "Height"),
+			Self::CompoundNormal => write!(f, "CompoundNormal"),
+			Self::Billboard => write!(f, "Billboard"),
+			Self::Cubemap => write!(f, "Cubemap"),
+			Self::Emission => write!(f, "Emission"),
+			Self::Projection => write!(f, "Projection"),
+			Self::Other(other) => write!(f, "{other}")
+		}
+	}
+}
+
+#[cfg(feature = "texture")]
+impl TextureSlotType {
+	/// Get the texture type a texture in this slot should have, if the slot has a specific type.
+	pub fn texture_type(&self) -> Option<crate::texture::TextureType> {
+		use crate::texture::TextureType;
+
+		match self {
+			Self::Colour => Some(TextureType::Colour),
+			Self::Normal => Some(TextureType::Normal),
+			Self::Height => Some(TextureType::Height),
+			Self::CompoundNormal => Some(TextureType::CompoundNormal),
+			Self::Billboard => Some(TextureType::Billboard),
+			Self::Cubemap => Some(TextureType::Cubemap),
+			Self::Emission => Some(TextureType::Emission),
+			Self::Projection => Some(TextureType::Projection),
+			Self::Default | Self::Other(_) => None
+		}
+	}
+
+	/// Get how a texture in this slot should be interpreted, if the slot has a specific type.
+	pub fn interpret_as(&self) -> Option<crate::texture::InterpretAs> {
+		use crate::texture::InterpretAs;
+
+		match self {
+			Self::Colour => Some(InterpretAs::Colour),
+			Self::Normal => Some(InterpretAs::Normal),
+			Self::Height => Some(InterpretAs::Height),
+			Self::CompoundNormal => Some(InterpretAs::CompoundNormal),
+			Self::Billboard => Some(InterpretAs::Billboard),
+			Self::Cubemap => Some(InterpretAs::Cubemap),
+			Self::Emission => Some(InterpretAs::Emission),
+			Self::Default | Self::Projection | Self::Other(_) => None
+		}
+	}
+}
+
+/// The type of a material property, as declared by a material class.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+#[cfg_attr(feature = "rune", derive(better_rune_derive::Any))]
+#[cfg_attr(feature = "rune", rune(item = ::glacier_formats::material))]
+#[cfg_attr(feature = "rune", rune_derive(DISPLAY_FMT, DEBUG_FMT, PARTIAL_EQ, EQ, CLONE))]
+pub enum MaterialPropertyType {
+	#[cfg_attr(feature = "rune", rune(constructor))]
+	Float,
+
+	/// A float vector of the given size.
+	#[cfg_attr(feature = "rune", rune(constructor))]
+	Vector(#[cfg_attr(feature = "rune", rune(get, set))] usize),
+
+	#[cfg_attr(feature = "rune", rune(constructor))]
+	Texture,
+
+	/// An RGB colour (COLO).
+	#[cfg_attr(feature = "rune", rune(constructor))]
+	Colour,
+
+	/// An RGBA colour (COL4).
+	#[cfg_attr(feature = "rune", rune(constructor))]
+	Colour4
+}
+
+impl Display for MaterialPropertyType {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Float => write!(f, "Float"),
+			Self::Vector(size) => write!(f, "Vector{size}"),
+			Self::Texture => write!(f, "Texture"),
+			Self::Colour => write!(f, "Colour"),
+			Self::Colour4 => write!(f, "Colour4")
+		}
+	}
+}
+
+impl MaterialPropertyValue {
+	/// Get the type of this property value.
+	pub fn property_type(&self) -> MaterialPropertyType {
+		match self {
+			Self::Float { .. } => MaterialPropertyType::Float,
+			Self::Vector { value, .. } => MaterialPropertyType::Vector(value.len()),
+			Self::Texture { .. } => MaterialPropertyType::Texture,
+			Self::Colour { value, .. } => {
+				if value.a.is_some() {
+					MaterialPropertyType::Colour4
+				} else {
+					MaterialPropertyType::Colour
+				}
+			}
+		}
+	}
+}
+
+impl MaterialInstance {
+	/// Parse a material instance (MATI).
+	#[try_fn]
+	#[cfg_attr(feature = "rune", rune::function(keep, path = Self::parse))]
+	#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+	pub fn parse(mati_data: &[u8], mati_metadata: &ResourceMetadata) -> Result<Self> {
+		let mut mati = Cursor::new(mati_data);
+
+		let header_offset = u32::from_le_bytes({
+			let mut x = [0u8; 4];
+			mati.read_exact(&mut x)?;
+			x
+		});
+
+		mati.seek(SeekFrom::Start(header_offset.into()))?;
+
+		let type_offset = u32::from_le_bytes({
+			let mut x = [0u8; 4];
+			mati.read_exact(&mut x)?;
+			x
+		});
+
+		let material_type = String::from_utf8(
+			mati_data
+				.iter()
+				.skip(type_offset as usize)
+				.take_while(|x| **x != 0)
+				.cloned()
+				.collect()
+		)?;
+
+		let mate_index = u32::from_le_bytes({
+			let mut x = [0u8; 4];
+			mati.read_exact(&mut x)?;
+			x
+		});
+
+		let class_flags = u32::from_le_bytes({
+			let mut x = [0u8; 4];
+			mati.read_exact(&mut x)?;
+			x
+		});
+
+		let instance_flags = u32::from_le_bytes({
+			let mut x = [0u8; 4];
+			mati.read_exact(&mut x)?;
+			x
+		});
+
+		let eres_index = u32::from_le_bytes({
+			let mut x = [0u8; 4];
+			mati.read_exact(&mut x)?;
+			x
+		});
+
+		let impact_material_index = u32::from_le_bytes({
+			let mut x = [0u8; 4];
+			mati.read_exact(&mut x)?;
+			x
+		});
+
+		let effect_resource_index = u32::from_le_bytes({
+			let mut x = [0u8; 4];
+			mati.read_exact(&mut x)?;
+			x
+		});
+
+		let instance_offset = u32::from_le_bytes({
+			let mut x = [0u8; 4];
+			mati.read_exact(&mut x)?;
+			x
+		});
+
+		let mut extra_header_words = vec![];
+		while mati.position() + 4 <= mati_data.len() as u64 {
+			extra_header_words.push(u32::from_le_bytes({
+				let mut x = [0u8; 4];
+				mati.read_exact(&mut x)?;
+				x
+			}));
+		}
+
+		let (name, tags, binder, unknown_instance_properties) = parse_instance(parse_material_property(
+			mati_data,
+			&mati_metadata.references,
+			instance_offset.into()
+		)?)?;
+
+		Self {
+			id: mati_metadata.id.to_owned(),
+			name,
+			material_type: material_type.parse()?,
+			tags,
+			class: mati_metadata.references.get(mate_index as usize).cloned(),
+			descriptor: mati_metadata.references.get(eres_index as usize).cloned(),
+			impact_material: mati_metadata.references.get(impact_material_index as usize).cloned(),
+			effect_resource: mati_metadata.references.get(effect_resource_index as usize).cloned(),
+			class_flags: ClassFlags::from_u32(class_flags),
+			instance_flags: InstanceFlags::from_u32(instance_flags),
+			extra_header_words: (extra_header_words != DEFAULT_HEADER_WORDS).then_some(extra_header_words),
+			unknown_instance_properties,
+			binder
+		}
+	}
+
+	/// Generate the game binary for this material instance.
+	#[try_fn]
+	#[cfg_attr(feature = "rune", rune::function(keep, instance))]
+	#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+	pub fn generate(self) -> Result<(Vec<u8>, ResourceMetadata)> {
+		let mut mati = vec![];
+		let mut mati_references = vec![];
+
+		// Header offset (placeholder)
+		mati.extend_from_slice(&0u32.to_le_bytes());
 
 		// Alignment
 		while mati.len() % 16 != 0 {
@@ -1534,11 +2077,15 @@ impl MaterialInstance {
 		}
 
 		// Generate instance data
-		let instance = IntermediateMaterialProperty::Instance(vec![
+		let mut instance_properties = vec![
 			IntermediateMaterialProperty::Name(self.name),
 			IntermediateMaterialProperty::Tags(self.tags),
 			to_intermediate(self.binder)?,
-		]);
+		];
+
+		insert_unknown_properties(&mut instance_properties, self.unknown_instance_properties);
+
+		let instance = IntermediateMaterialProperty::Instance(instance_properties);
 
 		let (instance_data, instance_resources) = generate_property(mati.len() as u32, &mut mati_references, instance)?;
 
@@ -1573,10 +2120,7 @@ impl MaterialInstance {
 		mati.extend_from_slice(&type_offset.to_le_bytes());
 
 		if let Some(class) = self.class {
-			mati_references.push(ResourceReference {
-				resource: class,
-				flags: ReferenceFlags::default()
-			});
+			mati_references.push(class);
 
 			// MATE index
 			mati.extend_from_slice(&(mati_references.len() as u32 - 1).to_le_bytes());
@@ -1592,14 +2136,7 @@ impl MaterialInstance {
 		mati.extend_from_slice(&self.instance_flags.as_u32().to_le_bytes());
 
 		if let Some(descriptor) = self.descriptor {
-			mati_references.push(ResourceReference {
-				resource: descriptor,
-				flags: ReferenceFlags {
-					reference_type: ReferenceType::Normal,
-					acquired: false,
-					language_code: 0b0001_1111
-				}
-			});
+			mati_references.push(descriptor);
 
 			// ERES index
 			mati.extend_from_slice(&(mati_references.len() as u32 - 1).to_le_bytes());
@@ -1608,19 +2145,24 @@ impl MaterialInstance {
 			mati.extend_from_slice(&u32::MAX.to_le_bytes());
 		}
 
-		// Skipped: lImpactMaterial, lEffectResource
-		mati.extend_from_slice(&[0u8; 8]);
+		for reference in [self.impact_material, self.effect_resource] {
+			if let Some(reference) = reference {
+				mati_references.push(reference);
+
+				// Impact material/effect resource index
+				mati.extend_from_slice(&(mati_references.len() as u32 - 1).to_le_bytes());
+			} else {
+				// Impact material/effect resource index
+				mati.extend_from_slice(&u32::MAX.to_le_bytes());
+			}
+		}
 
 		// Instance offset
 		mati.extend_from_slice(&instance_offset.to_le_bytes());
 
-		// Constant: 3
-		mati.extend_from_slice(&3u32.to_le_bytes());
-
-		// 12 zero bytes
-		mati.extend_from_slice(&0u32.to_le_bytes());
-		mati.extend_from_slice(&0u32.to_le_bytes());
-		mati.extend_from_slice(&0u32.to_le_bytes());
+		for word in self.extra_header_words.unwrap_or_else(|| DEFAULT_HEADER_WORDS.to_vec()) {
+			mati.extend_from_slice(&word.to_le_bytes());
+		}
 
 		(
 			mati,
@@ -1635,936 +2177,1959 @@ impl MaterialInstance {
 	}
 }
 
-#[try_fn]
-fn generate_property(
-	all_resources_offset: u32,
-	mati_references: &mut Vec<ResourceReference>,
-	property: IntermediateMaterialProperty
-) -> Result<(Vec<u8>, Option<Vec<u8>>)> {
-	match property {
-		IntermediateMaterialProperty::AlphaReference(val)
-		| IntermediateMaterialProperty::AlphaTestEnabled(val)
-		| IntermediateMaterialProperty::BlendEnabled(val)
-		| IntermediateMaterialProperty::DecalBlendDiffuse(val)
-		| IntermediateMaterialProperty::DecalBlendEmission(val)
-		| IntermediateMaterialProperty::DecalBlendNormal(val)
-		| IntermediateMaterialProperty::DecalBlendRoughness(val)
-		| IntermediateMaterialProperty::DecalBlendSpecular(val)
-		| IntermediateMaterialProperty::Enabled(val)
-		| IntermediateMaterialProperty::ZBias(val)
-		| IntermediateMaterialProperty::FogEnabled(val) => {
-			let name = match property {
-				IntermediateMaterialProperty::AlphaReference(_) => "AREF",
-				IntermediateMaterialProperty::AlphaTestEnabled(_) => "ATST",
-				IntermediateMaterialProperty::BlendEnabled(_) => "BENA",
-				IntermediateMaterialProperty::DecalBlendDiffuse(_) => "DBDE",
-				IntermediateMaterialProperty::DecalBlendEmission(_) => "DBEE",
-				IntermediateMaterialProperty::DecalBlendNormal(_) => "DBNE",
-				IntermediateMaterialProperty::DecalBlendRoughness(_) => "DBRE",
-				IntermediateMaterialProperty::DecalBlendSpecular(_) => "DBSE",
-				IntermediateMaterialProperty::Enabled(_) => "ENAB",
-				IntermediateMaterialProperty::ZBias(_) => "ZBIA",
-				IntermediateMaterialProperty::FogEnabled(_) => "FENA",
-				_ => unreachable!()
-			};
+/// A material class; a parsed MATE file.
+///
+/// This is the shader class referenced by material instances, which declares the properties they can set along with
+/// their default values.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "rune", serde_with::apply(_ => #[rune(get, set)]))]
+#[cfg_attr(feature = "rune", derive(better_rune_derive::Any))]
+#[cfg_attr(feature = "rune", rune(item = ::glacier_formats::material))]
+#[cfg_attr(feature = "rune", rune_derive(DEBUG_FMT, PARTIAL_EQ, CLONE))]
+#[cfg_attr(
+	feature = "rune",
+	rune_functions(Self::parse__meta, Self::generate__meta, Self::texture_slots__meta)
+)]
+pub struct MaterialClass {
+	pub id: RuntimeID,
 
-			let mut data = vec![];
+	/// The name of the shader class.
+	pub name: String,
 
-			data.extend_from_slice(&{
-				let mut x = name.as_bytes().to_owned();
-				x.reverse();
-				x
-			});
-			data.extend_from_slice(&val.to_le_bytes());
-			data.extend_from_slice(&[1, 0, 0, 0]); // Count (1 for this type)
-			data.extend_from_slice(&[2, 0, 0, 0]); // Type (2 for int)
+	#[cfg_attr(feature = "serde", serde(skip_serializing_if = "String::is_empty"))]
+	#[cfg_attr(feature = "serde", serde(default))]
+	pub tags: String,
 
-			(data, None)
-		}
+	/// Properties of the class (besides its name, tags and binder) not known to this library.
+	#[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Vec::is_empty"))]
+	pub unknown_instance_properties: Vec<UnknownMaterialProperty>,
 
-		IntermediateMaterialProperty::BlendMode(ref val)
-		| IntermediateMaterialProperty::CullingMode(ref val)
-		| IntermediateMaterialProperty::Name(ref val)
-		| IntermediateMaterialProperty::Tags(ref val)
-		| IntermediateMaterialProperty::TilingU(ref val)
-		| IntermediateMaterialProperty::TilingV(ref val)
-		| IntermediateMaterialProperty::Type(ref val) => {
-			let name = match property {
-				IntermediateMaterialProperty::BlendMode(_) => "BMOD",
-				IntermediateMaterialProperty::CullingMode(_) => "CULL",
-				IntermediateMaterialProperty::Name(_) => "NAME",
-				IntermediateMaterialProperty::Tags(_) => "TAGS",
-				IntermediateMaterialProperty::TilingU(_) => "TILU",
-				IntermediateMaterialProperty::TilingV(_) => "TILV",
-				IntermediateMaterialProperty::Type(_) => "TYPE",
-				_ => unreachable!()
-			};
+	/// The default render state and the properties declared by this class, with their default values.
+	#[cfg_attr(feature = "serde", serde(flatten))]
+	pub binder: Binder
+}
 
-			let mut data = vec![];
+impl MaterialClass {
+	/// Parse a material class (MATE).
+	#[try_fn]
+	#[cfg_attr(feature = "rune", rune::function(keep, path = Self::parse))]
+	#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+	pub fn parse(mate_data: &[u8], mate_metadata: &ResourceMetadata) -> Result<Self> {
+		let mut mate = Cursor::new(mate_data);
 
-			data.extend_from_slice(&{
-				let mut x = name.as_bytes().to_owned();
-				x.reverse();
-				x
-			});
-			data.extend_from_slice(&all_resources_offset.to_le_bytes()); // Pointer placeholder
-			data.extend_from_slice(&(val.len() as u32 + 1).to_le_bytes()); // Count (string length plus null terminator)
-			data.extend_from_slice(&[1, 0, 0, 0]); // Type (1 for string)
+		let header_offset = u32::from_le_bytes({
+			let mut x = [0u8; 4];
+			mate.read_exact(&mut x)?;
+			x
+		});
 
-			let mut resources = [val.as_bytes(), &[0]].concat();
+		mate.seek(SeekFrom::Start(header_offset.into()))?;
 
-			// Alignment
-			while resources.len() % 16 != 0 {
-				resources.push(0u8);
-			}
+		let instance_offset = u32::from_le_bytes({
+			let mut x = [0u8; 4];
+			mate.read_exact(&mut x)?;
+			x
+		});
+
+		let (name, tags, binder, unknown_instance_properties) = parse_instance(parse_material_property(
+			mate_data,
+			&mate_metadata.references,
+			instance_offset.into()
+		)?)?;
 
-			(data, Some(resources))
+		Self {
+			id: mate_metadata.id.to_owned(),
+			name,
+			tags,
+			unknown_instance_properties,
+			binder
 		}
+	}
 
-		IntermediateMaterialProperty::Binder(ref val)
-		| IntermediateMaterialProperty::Color(ref val)
-		| IntermediateMaterialProperty::Color4(ref val)
-		| IntermediateMaterialProperty::FloatValue(ref val)
-		| IntermediateMaterialProperty::Instance(ref val)
-		| IntermediateMaterialProperty::RenderState(ref val)
-		| IntermediateMaterialProperty::Texture(ref val) => {
-			let name = match property {
-				IntermediateMaterialProperty::Binder(_) => "BIND",
-				IntermediateMaterialProperty::Color(_) => "COLO",
-				IntermediateMaterialProperty::Color4(_) => "COL4",
-				IntermediateMaterialProperty::FloatValue(_) => "FLTV",
-				IntermediateMaterialProperty::Instance(_) => "INST",
-				IntermediateMaterialProperty::RenderState(_) => "RSTA",
-				IntermediateMaterialProperty::Texture(_) => "TEXT",
-				_ => unreachable!()
-			};
+	/// Generate the game binary for this material class.
+	#[try_fn]
+	#[cfg_attr(feature = "rune", rune::function(keep, instance))]
+	#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+	pub fn generate(self) -> Result<(Vec<u8>, ResourceMetadata)> {
+		let mut mate = vec![];
+		let mut mate_references = vec![];
 
-			let mut records = vec![];
-			let mut resources = vec![];
-			for sub_property in val.iter().cloned() {
-				let (record, resource) = generate_property(
-					all_resources_offset + (resources.len() as u32),
-					mati_references,
-					sub_property
-				)?;
+		// Header offset (placeholder)
+		mate.extend_from_slice(&0u32.to_le_bytes());
 
-				records.extend(record);
+		// Alignment
+		while mate.len() % 16 != 0 {
+			mate.push(0u8);
+		}
 
-				if let Some(res) = resource {
-					resources.extend(res);
-				}
-			}
+		let mut instance_properties = vec![
+			IntermediateMaterialProperty::Name(self.name),
+			IntermediateMaterialProperty::Tags(self.tags),
+			to_intermediate(self.binder)?,
+		];
 
-			let resource_chunk_size = resources.len() as u32;
+		insert_unknown_properties(&mut instance_properties, self.unknown_instance_properties);
 
-			let mut resources_concat = [resources, records].concat();
+		let instance = IntermediateMaterialProperty::Instance(instance_properties);
 
-			// Alignment
-			while resources_concat.len() % 16 != 0 {
-				resources_concat.push(0u8);
-			}
+		let (instance_data, instance_resources) = generate_property(mate.len() as u32, &mut mate_references, instance)?;
 
-			let mut data = vec![];
+		let Some(resources) = instance_resources else {
+			unreachable!()
+		};
 
-			data.extend_from_slice(&{
-				let mut x = name.as_bytes().to_owned();
-				x.reverse();
-				x
-			});
-			data.extend_from_slice(&(all_resources_offset + resource_chunk_size).to_le_bytes()); // Pointer
-			data.extend_from_slice(&(val.len() as u32).to_le_bytes()); // Count
-			data.extend_from_slice(&[3, 0, 0, 0]); // Type (3 for property)
+		mate.extend_from_slice(&resources);
+
+		let instance_offset = mate.len() as u32;
+
+		mate.extend_from_slice(&instance_data);
 
-			(data, Some(resources_concat))
+		// Alignment
+		while mate.len() % 16 != 0 {
+			mate.push(0u8);
 		}
 
-		IntermediateMaterialProperty::Opacity(val)
-		| IntermediateMaterialProperty::SubsurfaceValue(val)
-		| IntermediateMaterialProperty::SubsurfaceBlue(val)
-		| IntermediateMaterialProperty::SubsurfaceGreen(val)
-		| IntermediateMaterialProperty::SubsurfaceRed(val)
-		| IntermediateMaterialProperty::ZOffset(val) => {
-			let name = match property {
-				IntermediateMaterialProperty::Opacity(_) => "OPAC",
-				IntermediateMaterialProperty::ZOffset(_) => "ZOFF",
-				IntermediateMaterialProperty::SubsurfaceValue(_) => "SSBW",
-				IntermediateMaterialProperty::SubsurfaceBlue(_) => "SSVB",
-				IntermediateMaterialProperty::SubsurfaceGreen(_) => "SSVG",
-				IntermediateMaterialProperty::SubsurfaceRed(_) => "SSVR",
-				_ => unreachable!()
-			};
+		// Update header offset
+		let header_offset = (mate.len() as u32).to_le_bytes();
+		mate[0..4].copy_from_slice(&header_offset);
 
-			let mut data = vec![];
+		// Instance offset
+		mate.extend_from_slice(&instance_offset.to_le_bytes());
 
-			data.extend_from_slice(&{
-				let mut x = name.as_bytes().to_owned();
-				x.reverse();
-				x
-			});
-			data.extend_from_slice(&val.to_le_bytes());
-			data.extend_from_slice(&[1, 0, 0, 0]); // Count (1 for this type)
-			data.extend_from_slice(&[0, 0, 0, 0]); // Type (0 for float)
+		(
+			mate,
+			ResourceMetadata {
+				id: self.id,
+				resource_type: "MATE".try_into().unwrap(),
+				compressed: ResourceMetadata::infer_compressed("MATE".try_into().unwrap()),
+				scrambled: ResourceMetadata::infer_scrambled("MATE".try_into().unwrap()),
+				references: mate_references
+			}
+		)
+	}
 
-			(data, None)
-		}
+	/// Get the type of each property declared by this class.
+	pub fn property_types(&self) -> IndexMap<String, MaterialPropertyType> {
+		self.binder
+			.properties
+			.iter()
+			.map(|(name, value)| (name.to_owned(), value.property_type()))
+			.collect()
+	}
 
-		IntermediateMaterialProperty::TextureID(val) => {
-			let name = "TXID";
+	/// Get the texture slots declared by this class, as pairs of slot name and texture type.
+	#[cfg_attr(feature = "rune", rune::function(keep, instance))]
+	pub fn texture_slots(&self) -> Vec<(String, TextureSlotType)> {
+		self.binder
+			.properties
+			.iter()
+			.filter_map(|(name, value)| match value {
+				MaterialPropertyValue::Texture { texture_type, .. } => Some((name.to_owned(), texture_type.to_owned())),
+				_ => None
+			})
+			.collect()
+	}
+}
 
-			let mut data = vec![];
+/// A problem found when validating a material instance or entity against its material class.
+#[derive(Error, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "rune", derive(better_rune_derive::Any))]
+#[cfg_attr(feature = "rune", rune(item = ::glacier_formats::material))]
+#[cfg_attr(feature = "rune", rune_derive(DISPLAY_FMT, DEBUG_FMT, PARTIAL_EQ, CLONE))]
+pub enum MaterialValidationIssue {
+	#[error("property {0} is not declared by the material class")]
+	UndeclaredProperty(String),
+
+	#[error("property {property} should be {expected} but is {found}")]
+	TypeMismatch {
+		property: String,
+		expected: MaterialPropertyType,
+		found: MaterialPropertyType
+	},
 
-			data.extend_from_slice(&{
-				let mut x = name.as_bytes().to_owned();
-				x.reverse();
-				x
-			});
+	#[error("texture slot {property} should be type {expected} but is type {found}")]
+	TextureTypeMismatch {
+		property: String,
+		expected: TextureSlotType,
+		found: TextureSlotType
+	},
 
-			if let Some(id) = val {
-				mati_references.push(ResourceReference {
-					resource: id,
-					flags: ReferenceFlags {
-						reference_type: ReferenceType::Normal,
-						acquired: false,
-						language_code: 0b0001_1111
+	#[error("texture slot {property} has an unrecognised tiling mode: {value:?}")]
+	UnknownTilingMode { property: String, value: String },
+
+	#[error("texture slot {property} has an unrecognised type: {value:?}")]
+	UnknownTextureSlotType { property: String, value: String },
+
+	#[error("material entity references material {found} rather than {expected}")]
+	MaterialMismatch { expected: RuntimeID, found: RuntimeID }
+}
+
+impl MaterialInstance {
+	/// Check the properties of this material instance against those declared by its class, and report tiling modes and
+	/// texture slot types not known to this library (which are likely typos).
+	#[cfg_attr(feature = "rune", rune::function(keep, instance))]
+	pub fn validate(&self, class: &MaterialClass) -> Vec<MaterialValidationIssue> {
+		let mut issues = vec![];
+
+		for (name, value) in &self.binder.properties {
+			if let MaterialPropertyValue::Texture {
+				tiling_u,
+				tiling_v,
+				texture_type,
+				..
+			} = value
+			{
+				for tiling_mode in [tiling_u, tiling_v] {
+					if let TilingMode::Other(other) = tiling_mode {
+						issues.push(MaterialValidationIssue::UnknownTilingMode {
+							property: name.to_owned(),
+							value: other.to_owned()
+						});
 					}
-				});
+				}
 
-				data.extend_from_slice(&((mati_references.len() - 1) as u32).to_le_bytes());
-			} else {
-				data.extend_from_slice(&u32::MAX.to_le_bytes());
+				if let TextureSlotType::Other(other) = texture_type {
+					issues.push(MaterialValidationIssue::UnknownTextureSlotType {
+						property: name.to_owned(),
+						value: other.to_owned()
+					});
+				}
 			}
 
-			data.extend_from_slice(&[1, 0, 0, 0]); // Count (1 for this type)
-			data.extend_from_slice(&[2, 0, 0, 0]); // Type (2 for int)
+			let Some(declared) = class.binder.properties.get(name) else {
+				issues.push(MaterialValidationIssue::UndeclaredProperty(name.to_owned()));
+				continue;
+			};
 
-			(data, None)
+			if value.property_type() != declared.property_type() {
+				issues.push(MaterialValidationIssue::TypeMismatch {
+					property: name.to_owned(),
+					expected: declared.property_type(),
+					found: value.property_type()
+				});
+			} else if let MaterialPropertyValue::Texture { texture_type, .. } = value
+				&& let MaterialPropertyValue::Texture {
+					texture_type: expected, ..
+				} = declared && texture_type != expected
+			{
+				issues.push(MaterialValidationIssue::TextureTypeMismatch {
+					property: name.to_owned(),
+					expected: expected.to_owned(),
+					found: texture_type.to_owned()
+				});
+			}
 		}
 
-		IntermediateMaterialProperty::Value(val) => {
-			let name = "VALU";
+		issues
+	}
 
-			match val {
-				FloatVal::Single(val) => {
-					let mut data = vec![];
+	/// Apply the overrides of a material entity (which should reference this material instance) to get the effective
+	/// material instance. Overrides which don't match a property of the same type are skipped and returned as issues,
+	/// as is an entity which references a different material instance.
+	#[cfg_attr(feature = "rune", rune::function(keep, instance))]
+	pub fn resolve(&self, entity: &MaterialEntity) -> (Self, Vec<MaterialValidationIssue>) {
+		let mut resolved = self.to_owned();
+		let mut issues = vec![];
+
+		if entity.material != self.id {
+			issues.push(MaterialValidationIssue::MaterialMismatch {
+				expected: self.id,
+				found: entity.material
+			});
+		}
 
-					data.extend_from_slice(&{
-						let mut x = name.as_bytes().to_owned();
-						x.reverse();
-						x
-					});
-					data.extend_from_slice(&val.to_le_bytes());
-					data.extend_from_slice(&[1, 0, 0, 0]); // Count (1 for this type)
-					data.extend_from_slice(&[0, 0, 0, 0]); // Type (0 for float)
+		for (name, value) in &entity.overrides {
+			let Some(property) = resolved.binder.properties.get_mut(name) else {
+				issues.push(MaterialValidationIssue::UndeclaredProperty(name.to_owned()));
+				continue;
+			};
 
-					(data, None)
-				}
+			if value.property_type() != property.property_type() {
+				issues.push(MaterialValidationIssue::TypeMismatch {
+					property: name.to_owned(),
+					expected: property.property_type(),
+					found: value.property_type()
+				});
 
-				FloatVal::Vector(val) => {
-					let mut data = vec![];
+				continue;
+			}
 
-					data.extend_from_slice(&{
-						let mut x = name.as_bytes().to_owned();
-						x.reverse();
-						x
-					});
-					data.extend_from_slice(&all_resources_offset.to_le_bytes()); // Pointer placeholder
-					data.extend_from_slice(&(val.len() as u32).to_le_bytes()); // Count
-					data.extend_from_slice(&[0, 0, 0, 0]); // Type (0 for float)
+			match (property, value) {
+				(MaterialPropertyValue::Texture { value, .. }, MaterialOverride::Texture(texture)) => {
+					*value = *texture;
+				}
 
-					let mut resources = val.into_iter().flat_map(|x| x.to_le_bytes()).collect::<Vec<u8>>();
+				(MaterialPropertyValue::Colour { value, .. }, MaterialOverride::Color(colour)) => {
+					*value = *colour;
+				}
 
-					// Alignment
-					while resources.len() % 16 != 0 {
-						resources.push(0u8);
-					}
+				(MaterialPropertyValue::Float { value, .. }, MaterialOverride::Float(float)) => {
+					*value = *float;
+				}
 
-					(data, Some(resources))
+				(MaterialPropertyValue::Vector { value, .. }, MaterialOverride::Vector(vector)) => {
+					value.clone_from(vector);
 				}
+
+				_ => unreachable!()
 			}
 		}
+
+		(resolved, issues)
 	}
 }
 
-#[try_fn]
-fn parse_material_property(
-	mati_data: &[u8],
-	mati_references: &[ResourceReference],
-	start: u64
-) -> Result<IntermediateMaterialProperty> {
-	let mut mati = Cursor::new(mati_data);
-	mati.seek(SeekFrom::Start(start))?;
+impl MaterialEntity {
+	/// Check the overrides of this material entity against the properties declared by the material's class.
+	#[cfg_attr(feature = "rune", rune::function(keep, instance))]
+	pub fn validate(&self, class: &MaterialClass) -> Vec<MaterialValidationIssue> {
+		let mut issues = vec![];
 
-	let name = {
-		let mut x = [0u8; 4];
-		mati.read_exact(&mut x)?;
-		x.into_iter().rev().map(|x| x as char).collect::<String>()
-	};
+		for (name, value) in &self.overrides {
+			let Some(declared) = class.binder.properties.get(name) else {
+				issues.push(MaterialValidationIssue::UndeclaredProperty(name.to_owned()));
+				continue;
+			};
 
-	let data = {
-		let mut x = [0u8; 4];
-		mati.read_exact(&mut x)?;
-		x
-	};
+			if value.property_type() != declared.property_type() {
+				issues.push(MaterialValidationIssue::TypeMismatch {
+					property: name.to_owned(),
+					expected: declared.property_type(),
+					found: value.property_type()
+				});
+			}
+		}
 
-	let count = u32::from_le_bytes({
-		let mut x = [0u8; 4];
-		mati.read_exact(&mut x)?;
-		x
-	});
+		issues
+	}
+}
 
-	let ty = u32::from_le_bytes({
-		let mut x = [0u8; 4];
-		mati.read_exact(&mut x)?;
-		x
-	}) as u8;
+/// A glTF 2.0 material along with the images it references, as produced by [`MaterialInstance::to_gltf`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct GltfMaterialExport {
+	pub material: GltfMaterial,
 
-	match ty {
-		// Float value
-		0 => {
-			if count == 1 {
-				// Single float value
+	/// The image URIs used by the material. Texture indices in the material refer to the texture with the same index,
+	/// which should use the image at that index as its source.
+	pub images: Vec<String>,
 
-				let value = f32::from_le_bytes(data);
+	/// The names of properties which could not be mapped to the glTF material.
+	pub unmapped: Vec<String>
+}
 
-				match name.as_ref() {
-					"OPAC" => IntermediateMaterialProperty::Opacity(value),
-					"ZOFF" => IntermediateMaterialProperty::ZOffset(value),
-					"SSBW" => IntermediateMaterialProperty::SubsurfaceValue(value),
-					"SSVB" => IntermediateMaterialProperty::SubsurfaceBlue(value),
-					"SSVG" => IntermediateMaterialProperty::SubsurfaceGreen(value),
-					"SSVR" => IntermediateMaterialProperty::SubsurfaceRed(value),
-					"VALU" => IntermediateMaterialProperty::Value(FloatVal::Single(value)),
+/// A glTF 2.0 material object.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct GltfMaterial {
+	pub name: String,
 
-					_ => return Err(MaterialError::IncorrectType(name, ty))
-				}
-			} else {
-				// Vector
-				mati.seek(SeekFrom::Start(u32::from_le_bytes(data).into()))?;
+	pub pbr_metallic_roughness: GltfPbrMetallicRoughness,
 
-				let mut value = vec![];
-				for _ in 0..count {
-					value.push(f32::from_le_bytes({
-						let mut x = [0u8; 4];
-						mati.read_exact(&mut x)?;
-						x
+	#[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+	pub normal_texture: Option<GltfTextureInfo>,
+
+	#[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+	pub emissive_texture: Option<GltfTextureInfo>,
+
+	#[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "is_default"))]
+	pub emissive_factor: [f32; 3],
+
+	#[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "is_default"))]
+	pub alpha_mode: GltfAlphaMode,
+
+	#[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+	pub alpha_cutoff: Option<f32>,
+
+	#[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "std::ops::Not::not"))]
+	pub double_sided: bool,
+
+	#[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "is_default"))]
+	pub extensions: GltfMaterialExtensions
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct GltfPbrMetallicRoughness {
+	pub base_color_factor: [f32; 4],
+
+	#[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+	pub base_color_texture: Option<GltfTextureInfo>,
+
+	pub metallic_factor: f32,
+	pub roughness_factor: f32
+}
+
+impl Default for GltfPbrMetallicRoughness {
+	fn default() -> Self {
+		Self {
+			base_color_factor: [1.0, 1.0, 1.0, 1.0],
+			base_color_texture: None,
+			metallic_factor: 0.0,
+			roughness_factor: 1.0
+		}
+	}
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct GltfTextureInfo {
+	pub index: usize
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "UPPERCASE"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum GltfAlphaMode {
+	#[default]
+	Opaque,
+	Mask,
+	Blend
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct GltfMaterialExtensions {
+	#[cfg_attr(feature = "serde", serde(rename = "KHR_materials_specular"))]
+	#[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+	pub khr_materials_specular: Option<GltfSpecular>
+}
+
+/// The KHR_materials_specular extension.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct GltfSpecular {
+	#[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+	pub specular_color_texture: Option<GltfTextureInfo>,
+
+	#[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+	pub specular_color_factor: Option<[f32; 3]>
+}
+
+/// A glTF factor which a colour property can be mapped to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum GltfColourSlot {
+	BaseColour,
+	Specular,
+	Emissive
+}
+
+impl GltfColourSlot {
+	/// Colour properties have no slot type, so they're mapped by name.
+	fn from_property_name(name: &str) -> Option<Self> {
+		let name = name.to_lowercase();
+
+		if name.contains("diffuse") || name.contains("albedo") || name.contains("basecolo") {
+			Some(Self::BaseColour)
+		} else if name.contains("specular") {
+			Some(Self::Specular)
+		} else if name.contains("emissive") || name.contains("emission") {
+			Some(Self::Emissive)
+		} else {
+			None
+		}
+	}
+}
+
+impl MaterialInstance {
+	/// Convert this material instance to a glTF 2.0 material.
+	///
+	/// Textures are mapped by their slot type to the base colour, normal and emissive textures, and colour properties
+	/// are mapped by name to the base colour, emissive and (through KHR_materials_specular) specular factors. Textures
+	/// are resolved to image URIs with `resolve_path`; any properties which couldn't be mapped or resolved are listed in
+	/// the result.
+	#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+	pub fn to_gltf(&self, mut resolve_path: impl FnMut(RuntimeID) -> Option<String>) -> GltfMaterialExport {
+		let render_state = &self.binder.render_state;
+
+		let mut export = GltfMaterialExport {
+			material: GltfMaterial {
+				name: self.name.to_owned(),
+				double_sided: render_state.culling_mode == CullingMode::TwoSided,
+				..Default::default()
+			},
+			images: vec![],
+			unmapped: vec![]
+		};
+
+		if render_state.alpha_test_enabled == Some(true) {
+			export.material.alpha_mode = GltfAlphaMode::Mask;
+			export.material.alpha_cutoff = Some(render_state.alpha_reference.unwrap_or(0) as f32 / 255.0);
+		} else if render_state.blend_enabled != Some(false)
+			&& render_state.blend_mode.is_some_and(|x| x != BlendMode::Opaque)
+		{
+			export.material.alpha_mode = GltfAlphaMode::Blend;
+		}
+
+		let mut has_emissive_factor = false;
+
+		for (name, value) in &self.binder.properties {
+			let mapped = match value {
+				MaterialPropertyValue::Texture {
+					enabled: true,
+					value: Some(texture),
+					texture_type,
+					..
+				} => {
+					let target = match texture_type {
+						TextureSlotType::Colour => Some(&mut export.material.pbr_metallic_roughness.base_color_texture),
+						TextureSlotType::Normal | TextureSlotType::CompoundNormal => {
+							Some(&mut export.material.normal_texture)
+						}
+						TextureSlotType::Emission => Some(&mut export.material.emissive_texture),
+						_ => None
+					};
+
+					if let Some(target) = target
+						&& target.is_none()
+						&& let Some(uri) = resolve_path(*texture)
+					{
+						let index = export.images.iter().position(|x| *x == uri).unwrap_or_else(|| {
+							export.images.push(uri);
+							export.images.len() - 1
+						});
+
+						*target = Some(GltfTextureInfo { index });
+
+						true
+					} else {
+						false
+					}
+				}
+
+				MaterialPropertyValue::Colour {
+					enabled: true, value, ..
+				} => match GltfColourSlot::from_property_name(name) {
+					Some(GltfColourSlot::BaseColour) => {
+						export.material.pbr_metallic_roughness.base_color_factor =
+							[value.r, value.g, value.b, value.a.unwrap_or(1.0)];
+
+						true
+					}
+
+					Some(GltfColourSlot::Emissive) => {
+						export.material.emissive_factor = [value.r, value.g, value.b];
+						has_emissive_factor = true;
+
+						true
+					}
+
+					Some(GltfColourSlot::Specular) => {
+						export
+							.material
+							.extensions
+							.khr_materials_specular
+							.get_or_insert_default()
+							.specular_color_factor = Some([value.r, value.g, value.b]);
+
+						true
+					}
+
+					None => false
+				},
+
+				_ => false
+			};
+
+			if !mapped {
+				export.unmapped.push(name.to_owned());
+			}
+		}
+
+		// glTF multiplies the emissive texture by the emissive factor, which would otherwise leave it black
+		if export.material.emissive_texture.is_some() && !has_emissive_factor {
+			export.material.emissive_factor = [1.0, 1.0, 1.0];
+		}
+
+		export
+	}
+}
+
+#[try_fn]
+fn generate_property(
+	all_resources_offset: u32,
+	mati_references: &mut Vec<ResourceReference>,
+	property: IntermediateMaterialProperty
+) -> Result<(Vec<u8>, Option<Vec<u8>>)> {
+	match property {
+		IntermediateMaterialProperty::AlphaReference(val)
+		| IntermediateMaterialProperty::AlphaTestEnabled(val)
+		| IntermediateMaterialProperty::BlendEnabled(val)
+		| IntermediateMaterialProperty::DecalBlendDiffuse(val)
+		| IntermediateMaterialProperty::DecalBlendEmission(val)
+		| IntermediateMaterialProperty::DecalBlendNormal(val)
+		| IntermediateMaterialProperty::DecalBlendRoughness(val)
+		| IntermediateMaterialProperty::DecalBlendSpecular(val)
+		| IntermediateMaterialProperty::Enabled(val)
+		| IntermediateMaterialProperty::ZBias(val)
+		| IntermediateMaterialProperty::FogEnabled(val) => {
+			let name = match property {
+				IntermediateMaterialProperty::AlphaReference(_) => "AREF",
+				IntermediateMaterialProperty::AlphaTestEnabled(_) => "ATST",
+				IntermediateMaterialProperty::BlendEnabled(_) => "BENA",
+				IntermediateMaterialProperty::DecalBlendDiffuse(_) => "DBDE",
+				IntermediateMaterialProperty::DecalBlendEmission(_) => "DBEE",
+				IntermediateMaterialProperty::DecalBlendNormal(_) => "DBNE",
+				IntermediateMaterialProperty::DecalBlendRoughness(_) => "DBRE",
+				IntermediateMaterialProperty::DecalBlendSpecular(_) => "DBSE",
+				IntermediateMaterialProperty::Enabled(_) => "ENAB",
+				IntermediateMaterialProperty::ZBias(_) => "ZBIA",
+				IntermediateMaterialProperty::FogEnabled(_) => "FENA",
+				_ => unreachable!()
+			};
+
+			let mut data = vec![];
+
+			data.extend_from_slice(&{
+				let mut x = name.as_bytes().to_owned();
+				x.reverse();
+				x
+			});
+			data.extend_from_slice(&val.to_le_bytes());
+			data.extend_from_slice(&[1, 0, 0, 0]); // Count (1 for this type)
+			data.extend_from_slice(&[2, 0, 0, 0]); // Type (2 for int)
+
+			(data, None)
+		}
+
+		IntermediateMaterialProperty::BlendMode(ref val)
+		| IntermediateMaterialProperty::CullingMode(ref val)
+		| IntermediateMaterialProperty::Name(ref val)
+		| IntermediateMaterialProperty::Tags(ref val)
+		| IntermediateMaterialProperty::TilingU(ref val)
+		| IntermediateMaterialProperty::TilingV(ref val)
+		| IntermediateMaterialProperty::Type(ref val) => {
+			let name = match property {
+				IntermediateMaterialProperty::BlendMode(_) => "BMOD",
+				IntermediateMaterialProperty::CullingMode(_) => "CULL",
+				IntermediateMaterialProperty::Name(_) => "NAME",
+				IntermediateMaterialProperty::Tags(_) => "TAGS",
+				IntermediateMaterialProperty::TilingU(_) => "TILU",
+				IntermediateMaterialProperty::TilingV(_) => "TILV",
+				IntermediateMaterialProperty::Type(_) => "TYPE",
+				_ => unreachable!()
+			};
+
+			generate_string_property(name, all_resources_offset, val)
+		}
+
+		IntermediateMaterialProperty::Binder(ref val)
+		| IntermediateMaterialProperty::Color(ref val)
+		| IntermediateMaterialProperty::Color4(ref val)
+		| IntermediateMaterialProperty::FloatValue(ref val)
+		| IntermediateMaterialProperty::Instance(ref val)
+		| IntermediateMaterialProperty::RenderState(ref val)
+		| IntermediateMaterialProperty::Texture(ref val) => {
+			let name = match property {
+				IntermediateMaterialProperty::Binder(_) => "BIND",
+				IntermediateMaterialProperty::Color(_) => "COLO",
+				IntermediateMaterialProperty::Color4(_) => "COL4",
+				IntermediateMaterialProperty::FloatValue(_) => "FLTV",
+				IntermediateMaterialProperty::Instance(_) => "INST",
+				IntermediateMaterialProperty::RenderState(_) => "RSTA",
+				IntermediateMaterialProperty::Texture(_) => "TEXT",
+				_ => unreachable!()
+			};
+
+			generate_nested_property(name, all_resources_offset, mati_references, val)?
+		}
+
+		IntermediateMaterialProperty::Opacity(val)
+		| IntermediateMaterialProperty::SubsurfaceValue(val)
+		| IntermediateMaterialProperty::SubsurfaceBlue(val)
+		| IntermediateMaterialProperty::SubsurfaceGreen(val)
+		| IntermediateMaterialProperty::SubsurfaceRed(val)
+		| IntermediateMaterialProperty::ZOffset(val) => {
+			let name = match property {
+				IntermediateMaterialProperty::Opacity(_) => "OPAC",
+				IntermediateMaterialProperty::ZOffset(_) => "ZOFF",
+				IntermediateMaterialProperty::SubsurfaceValue(_) => "SSBW",
+				IntermediateMaterialProperty::SubsurfaceBlue(_) => "SSVB",
+				IntermediateMaterialProperty::SubsurfaceGreen(_) => "SSVG",
+				IntermediateMaterialProperty::SubsurfaceRed(_) => "SSVR",
+				_ => unreachable!()
+			};
+
+			let mut data = vec![];
+
+			data.extend_from_slice(&{
+				let mut x = name.as_bytes().to_owned();
+				x.reverse();
+				x
+			});
+			data.extend_from_slice(&val.to_le_bytes());
+			data.extend_from_slice(&[1, 0, 0, 0]); // Count (1 for this type)
+			data.extend_from_slice(&[0, 0, 0, 0]); // Type (0 for float)
+
+			(data, None)
+		}
+
+		IntermediateMaterialProperty::TextureID(val) => {
+			let name = "TXID";
+
+			let mut data = vec![];
+
+			data.extend_from_slice(&{
+				let mut x = name.as_bytes().to_owned();
+				x.reverse();
+				x
+			});
+
+			if let Some(id) = val {
+				mati_references.push(ResourceReference {
+					resource: id,
+					flags: ReferenceFlags {
+						reference_type: ReferenceType::Normal,
+						acquired: false,
+						language_code: 0b0001_1111
+					}
+				});
+
+				data.extend_from_slice(&((mati_references.len() - 1) as u32).to_le_bytes());
+			} else {
+				data.extend_from_slice(&u32::MAX.to_le_bytes());
+			}
+
+			data.extend_from_slice(&[1, 0, 0, 0]); // Count (1 for this type)
+			data.extend_from_slice(&[2, 0, 0, 0]); // Type (2 for int)
+
+			(data, None)
+		}
+
+		IntermediateMaterialProperty::Value(val) => {
+			let name = "VALU";
+
+			match val {
+				FloatVal::Single(val) => {
+					let mut data = vec![];
+
+					data.extend_from_slice(&{
+						let mut x = name.as_bytes().to_owned();
+						x.reverse();
+						x
+					});
+					data.extend_from_slice(&val.to_le_bytes());
+					data.extend_from_slice(&[1, 0, 0, 0]); // Count (1 for this type)
+					data.extend_from_slice(&[0, 0, 0, 0]); // Type (0 for float)
+
+					(data, None)
+				}
+
+				FloatVal::Vector(val) => generate_vector_property(name, all_resources_offset, &val)
+			}
+		}
+
+		IntermediateMaterialProperty::Unknown(UnknownMaterialProperty { tag, data: value, .. }) => match value {
+			UnknownPropertyData::Float(val) => {
+				let mut data = vec![];
+
+				data.extend(tag_bytes(&tag));
+				data.extend_from_slice(&val.to_le_bytes());
+				data.extend_from_slice(&[1, 0, 0, 0]); // Count (1 for this type)
+				data.extend_from_slice(&[0, 0, 0, 0]); // Type (0 for float)
+
+				(data, None)
+			}
+
+			UnknownPropertyData::Int(val) => {
+				let mut data = vec![];
+
+				data.extend(tag_bytes(&tag));
+				data.extend_from_slice(&val.to_le_bytes());
+				data.extend_from_slice(&[1, 0, 0, 0]); // Count (1 for this type)
+				data.extend_from_slice(&[2, 0, 0, 0]); // Type (2 for int)
+
+				(data, None)
+			}
+
+			UnknownPropertyData::Vector(val) => generate_vector_property(&tag, all_resources_offset, &val),
+
+			UnknownPropertyData::String(val) => generate_string_property(&tag, all_resources_offset, &val),
+
+			UnknownPropertyData::Properties(val) => {
+				generate_nested_property(&tag, all_resources_offset, mati_references, &val)?
+			}
+		}
+	}
+}
+
+/// Property tags are stored reversed, one byte per character.
+fn tag_bytes(name: &str) -> impl Iterator<Item = u8> {
+	name.chars().rev().map(|x| x as u8)
+}
+
+fn generate_string_property(name: &str, all_resources_offset: u32, val: &str) -> (Vec<u8>, Option<Vec<u8>>) {
+	let mut data = vec![];
+
+	data.extend(tag_bytes(name));
+	data.extend_from_slice(&all_resources_offset.to_le_bytes()); // Pointer placeholder
+	data.extend_from_slice(&(val.len() as u32 + 1).to_le_bytes()); // Count (string length plus null terminator)
+	data.extend_from_slice(&[1, 0, 0, 0]); // Type (1 for string)
+
+	let mut resources = [val.as_bytes(), &[0]].concat();
+
+	// Alignment
+	while resources.len() % 16 != 0 {
+		resources.push(0u8);
+	}
+
+	(data, Some(resources))
+}
+
+fn generate_vector_property(name: &str, all_resources_offset: u32, val: &[f32]) -> (Vec<u8>, Option<Vec<u8>>) {
+	let mut data = vec![];
+
+	data.extend(tag_bytes(name));
+	data.extend_from_slice(&all_resources_offset.to_le_bytes()); // Pointer placeholder
+	data.extend_from_slice(&(val.len() as u32).to_le_bytes()); // Count
+	data.extend_from_slice(&[0, 0, 0, 0]); // Type (0 for float)
+
+	let mut resources = val.iter().flat_map(|x| x.to_le_bytes()).collect::<Vec<u8>>();
+
+	// Alignment
+	while resources.len() % 16 != 0 {
+		resources.push(0u8);
+	}
+
+	(data, Some(resources))
+}
+
+#[try_fn]
+fn generate_nested_property(
+	name: &str,
+	all_resources_offset: u32,
+	mati_references: &mut Vec<ResourceReference>,
+	val: &[IntermediateMaterialProperty]
+) -> Result<(Vec<u8>, Option<Vec<u8>>)> {
+	let mut records = vec![];
+	let mut resources = vec![];
+	for sub_property in val.iter().cloned() {
+		let (record, resource) = generate_property(
+			all_resources_offset + (resources.len() as u32),
+			mati_references,
+			sub_property
+		)?;
+
+		records.extend(record);
+
+		if let Some(res) = resource {
+			resources.extend(res);
+		}
+	}
+
+	let resource_chunk_size = resources.len() as u32;
+
+	let mut resources_concat = [resources, records].concat();
+
+	// Alignment
+	while resources_concat.len() % 16 != 0 {
+		resources_concat.push(0u8);
+	}
+
+	let mut data = vec![];
+
+	data.extend(tag_bytes(name));
+	data.extend_from_slice(&(all_resources_offset + resource_chunk_size).to_le_bytes()); // Pointer
+	data.extend_from_slice(&(val.len() as u32).to_le_bytes()); // Count
+	data.extend_from_slice(&[3, 0, 0, 0]); // Type (3 for property)
+
+	(data, Some(resources_concat))
+}
+
+#[try_fn]
+fn parse_material_property(
+	mati_data: &[u8],
+	mati_references: &[ResourceReference],
+	start: u64
+) -> Result<IntermediateMaterialProperty> {
+	let mut mati = Cursor::new(mati_data);
+	mati.seek(SeekFrom::Start(start))?;
+
+	let name = {
+		let mut x = [0u8; 4];
+		mati.read_exact(&mut x)?;
+		x.into_iter().rev().map(|x| x as char).collect::<String>()
+	};
+
+	let data = {
+		let mut x = [0u8; 4];
+		mati.read_exact(&mut x)?;
+		x
+	};
+
+	let count = u32::from_le_bytes({
+		let mut x = [0u8; 4];
+		mati.read_exact(&mut x)?;
+		x
+	});
+
+	let ty = u32::from_le_bytes({
+		let mut x = [0u8; 4];
+		mati.read_exact(&mut x)?;
+		x
+	}) as u8;
+
+	match ty {
+		// Float value
+		0 => {
+			if count == 1 {
+				// Single float value
+
+				let value = f32::from_le_bytes(data);
+
+				match name.as_ref() {
+					"OPAC" => IntermediateMaterialProperty::Opacity(value),
+					"ZOFF" => IntermediateMaterialProperty::ZOffset(value),
+					"SSBW" => IntermediateMaterialProperty::SubsurfaceValue(value),
+					"SSVB" => IntermediateMaterialProperty::SubsurfaceBlue(value),
+					"SSVG" => IntermediateMaterialProperty::SubsurfaceGreen(value),
+					"SSVR" => IntermediateMaterialProperty::SubsurfaceRed(value),
+					"VALU" => IntermediateMaterialProperty::Value(FloatVal::Single(value)),
+
+					_ => IntermediateMaterialProperty::Unknown(UnknownMaterialProperty {
+						tag: name,
+						data: UnknownPropertyData::Float(value),
+						position: None
+					})
+				}
+			} else {
+				// Vector
+				mati.seek(SeekFrom::Start(u32::from_le_bytes(data).into()))?;
+
+				let mut value = vec![];
+				for _ in 0..count {
+					value.push(f32::from_le_bytes({
+						let mut x = [0u8; 4];
+						mati.read_exact(&mut x)?;
+						x
 					}));
 				}
 
-				match name.as_ref() {
-					"VALU" => IntermediateMaterialProperty::Value(FloatVal::Vector(value)),
+				match name.as_ref() {
+					"VALU" => IntermediateMaterialProperty::Value(FloatVal::Vector(value)),
+
+					_ => IntermediateMaterialProperty::Unknown(UnknownMaterialProperty {
+						tag: name,
+						data: UnknownPropertyData::Vector(value),
+						position: None
+					})
+				}
+			}
+		}
+
+		// String value
+		1 => {
+			let value = String::from_utf8(
+				mati_data
+					.iter()
+					.skip(u32::from_le_bytes(data) as usize)
+					.take_while(|x| **x != 0)
+					.cloned()
+					.collect()
+			)?;
+
+			match name.as_ref() {
+				"BMOD" => IntermediateMaterialProperty::BlendMode(value),
+				"CULL" => IntermediateMaterialProperty::CullingMode(value),
+				"NAME" => IntermediateMaterialProperty::Name(value),
+				"TAGS" => IntermediateMaterialProperty::Tags(value),
+				"TILU" => IntermediateMaterialProperty::TilingU(value),
+				"TILV" => IntermediateMaterialProperty::TilingV(value),
+				"TYPE" => IntermediateMaterialProperty::Type(value),
+
+				_ => IntermediateMaterialProperty::Unknown(UnknownMaterialProperty {
+					tag: name,
+					data: UnknownPropertyData::String(value),
+					position: None
+				})
+			}
+		}
+
+		// Int value
+		2 => {
+			let value = u32::from_le_bytes(data);
+
+			match name.as_ref() {
+				"AREF" => IntermediateMaterialProperty::AlphaReference(value),
+				"ATST" => IntermediateMaterialProperty::AlphaTestEnabled(value),
+				"BENA" => IntermediateMaterialProperty::BlendEnabled(value),
+				"DBDE" => IntermediateMaterialProperty::DecalBlendDiffuse(value),
+				"DBEE" => IntermediateMaterialProperty::DecalBlendEmission(value),
+				"DBNE" => IntermediateMaterialProperty::DecalBlendNormal(value),
+				"DBRE" => IntermediateMaterialProperty::DecalBlendRoughness(value),
+				"DBSE" => IntermediateMaterialProperty::DecalBlendSpecular(value),
+				"ENAB" => IntermediateMaterialProperty::Enabled(value),
+				"FENA" => IntermediateMaterialProperty::FogEnabled(value),
+				"ZBIA" => IntermediateMaterialProperty::ZBias(value),
+
+				"TXID" => IntermediateMaterialProperty::TextureID(if value != 4294967295 {
+					Some(
+						mati_references
+							.get(value as usize)
+							.ok_or(MaterialError::InvalidDependency(value as usize))?
+							.resource
+							.to_owned()
+					)
+				} else {
+					None
+				}),
+
+				_ => IntermediateMaterialProperty::Unknown(UnknownMaterialProperty {
+					tag: name,
+					data: UnknownPropertyData::Int(value),
+					position: None
+				})
+			}
+		}
+
+		// Property value
+		3 => {
+			mati.seek(SeekFrom::Start(u32::from_le_bytes(data).into()))?;
+
+			let mut values = vec![];
+			for _ in 0..count {
+				values.push(parse_material_property(mati_data, mati_references, mati.position())?);
+				mati.seek(SeekFrom::Current(0x10))?;
+			}
+
+			match name.as_ref() {
+				"BIND" => IntermediateMaterialProperty::Binder(values),
+				"COLO" => IntermediateMaterialProperty::Color(values),
+				"COL4" => IntermediateMaterialProperty::Color4(values),
+				"FLTV" => IntermediateMaterialProperty::FloatValue(values),
+				"INST" => IntermediateMaterialProperty::Instance(values),
+				"RSTA" => IntermediateMaterialProperty::RenderState(values),
+				"TEXT" => IntermediateMaterialProperty::Texture(values),
+
+				_ => IntermediateMaterialProperty::Unknown(UnknownMaterialProperty {
+					tag: name,
+					data: UnknownPropertyData::Properties(values),
+					position: None
+				})
+			}
+		}
+
+		_ => return Err(MaterialError::UnrecognisedEntryType(ty))
+	}
+}
+
+#[try_fn]
+fn parse_instance(
+	instance: IntermediateMaterialProperty
+) -> Result<(String, String, Binder, Vec<UnknownMaterialProperty>)> {
+	let IntermediateMaterialProperty::Instance(properties) = instance else {
+		return Err(MaterialError::InstanceNotTopLevel);
+	};
+
+	(
+		properties
+			.iter()
+			.find_map(|x| match x {
+				IntermediateMaterialProperty::Name(x) => Some(x),
+				_ => None
+			})
+			.ok_or_else(|| MaterialError::RequiredPropertyNotFound("NAME".into()))?
+			.to_owned(),
+		properties
+			.iter()
+			.find_map(|x| match x {
+				IntermediateMaterialProperty::Tags(x) => Some(x),
+				_ => None
+			})
+			.ok_or_else(|| MaterialError::RequiredPropertyNotFound("TAGS".into()))?
+			.to_owned(),
+		{
+			let binder = properties
+				.iter()
+				.find_map(|x| match x {
+					IntermediateMaterialProperty::Binder(x) => Some(x),
+					_ => None
+				})
+				.ok_or_else(|| MaterialError::RequiredPropertyNotFound("BIND".into()))?;
+
+			let mut properties = IndexMap::new();
+			let mut unknown_sub_properties = IndexMap::new();
+
+			for property in binder.iter().filter(|x| {
+				!matches!(
+					x,
+					IntermediateMaterialProperty::RenderState(_) | IntermediateMaterialProperty::Unknown(_)
+				)
+			}) {
+				let (name, value) = parse_binder_property(property)?;
+
+				if let IntermediateMaterialProperty::FloatValue(sub_properties)
+				| IntermediateMaterialProperty::Texture(sub_properties)
+				| IntermediateMaterialProperty::Color(sub_properties)
+				| IntermediateMaterialProperty::Color4(sub_properties) = property
+				{
+					let unknown = collect_unknown_properties(sub_properties);
+
+					if !unknown.is_empty() {
+						unknown_sub_properties.insert(name.to_owned(), unknown);
+					}
+				}
+
+				properties.insert(name, value);
+			}
+
+			Binder {
+				render_state: {
+					let props = binder
+						.iter()
+						.find_map(|x| match x {
+							IntermediateMaterialProperty::RenderState(x) => Some(x),
+							_ => None
+						})
+						.ok_or_else(|| MaterialError::RequiredPropertyNotFound("RSTA".into()))?
+						.to_owned();
+
+					RenderState {
+						name: props.iter().find_map(|x| match x {
+							IntermediateMaterialProperty::Name(x) => Some(x.to_owned()),
+							_ => None
+						}),
+						enabled: props.iter().find_map(|x| match *x {
+							IntermediateMaterialProperty::Enabled(x) => Some(x != 0),
+							_ => None
+						}),
+						blend_enabled: props.iter().find_map(|x| match *x {
+							IntermediateMaterialProperty::BlendEnabled(x) => Some(x != 0),
+							_ => None
+						}),
+						blend_mode: props
+							.iter()
+							.find_map(|x| match *x {
+								IntermediateMaterialProperty::BlendMode(ref x) => Some(x.parse()),
+								_ => None
+							})
+							.transpose()?,
+						decal_blend_diffuse: props.iter().find_map(|x| match *x {
+							IntermediateMaterialProperty::DecalBlendDiffuse(x) => Some(x),
+							_ => None
+						}),
+						decal_blend_normal: props.iter().find_map(|x| match *x {
+							IntermediateMaterialProperty::DecalBlendNormal(x) => Some(x),
+							_ => None
+						}),
+						decal_blend_specular: props.iter().find_map(|x| match *x {
+							IntermediateMaterialProperty::DecalBlendSpecular(x) => Some(x),
+							_ => None
+						}),
+						decal_blend_roughness: props.iter().find_map(|x| match *x {
+							IntermediateMaterialProperty::DecalBlendRoughness(x) => Some(x),
+							_ => None
+						}),
+						decal_blend_emission: props.iter().find_map(|x| match *x {
+							IntermediateMaterialProperty::DecalBlendEmission(x) => Some(x),
+							_ => None
+						}),
+						alpha_test_enabled: props.iter().find_map(|x| match *x {
+							IntermediateMaterialProperty::AlphaTestEnabled(x) => Some(x != 0),
+							_ => None
+						}),
+						alpha_reference: props.iter().find_map(|x| match *x {
+							IntermediateMaterialProperty::AlphaReference(x) => Some(x),
+							_ => None
+						}),
+						fog_enabled: props.iter().find_map(|x| match *x {
+							IntermediateMaterialProperty::FogEnabled(x) => Some(x != 0),
+							_ => None
+						}),
+						opacity: props.iter().find_map(|x| match *x {
+							IntermediateMaterialProperty::Opacity(x) => Some(x),
+							_ => None
+						}),
+						culling_mode: props
+							.iter()
+							.find_map(|x| match *x {
+								IntermediateMaterialProperty::CullingMode(ref x) => Some(x.parse()),
+								_ => None
+							})
+							.transpose()?
+							.ok_or_else(|| MaterialError::RequiredPropertyNotFound("CULL".into()))?,
+						z_bias: props.iter().find_map(|x| match *x {
+							IntermediateMaterialProperty::ZBias(x) => Some(x),
+							_ => None
+						}),
+						z_offset: props.iter().find_map(|x| match *x {
+							IntermediateMaterialProperty::ZOffset(x) => Some(x),
+							_ => None
+						}),
+						subsurface_red: props.iter().find_map(|x| match *x {
+							IntermediateMaterialProperty::SubsurfaceRed(x) => Some(x),
+							_ => None
+						}),
+						subsurface_green: props.iter().find_map(|x| match *x {
+							IntermediateMaterialProperty::SubsurfaceGreen(x) => Some(x),
+							_ => None
+						}),
+						subsurface_blue: props.iter().find_map(|x| match *x {
+							IntermediateMaterialProperty::SubsurfaceBlue(x) => Some(x),
+							_ => None
+						}),
+						subsurface_value: props.iter().find_map(|x| match *x {
+							IntermediateMaterialProperty::SubsurfaceValue(x) => Some(x),
+							_ => None
+						}),
+						unknown_properties: collect_unknown_properties(&props)
+					}
+				},
+
+				properties,
+				unknown_properties: collect_unknown_properties(binder),
+				unknown_sub_properties
+			}
+		},
+		collect_unknown_properties(&properties)
+	)
+}
+
+/// Parse a property of a binder: a float or vector (FLTV), texture (TEXT) or colour (COLO/COL4).
+#[try_fn]
+fn parse_binder_property(property: &IntermediateMaterialProperty) -> Result<(String, MaterialPropertyValue)> {
+	match property {
+		IntermediateMaterialProperty::FloatValue(x) => {
+			let name = x
+				.iter()
+				.find_map(|x| match x {
+					IntermediateMaterialProperty::Name(x) => Some(x),
+					_ => None
+				})
+				.ok_or_else(|| MaterialError::RequiredPropertyNotFound("NAME".into()))?;
+
+			let enabled = x
+				.iter()
+				.find_map(|x| match x {
+					IntermediateMaterialProperty::Enabled(x) => Some(x),
+					_ => None
+				})
+				.ok_or_else(|| MaterialError::RequiredPropertyNotFound("ENAB".into()))?;
+
+			let value = x
+				.iter()
+				.find_map(|x| match x {
+					IntermediateMaterialProperty::Value(x) => Some(x),
+					_ => None
+				})
+				.ok_or_else(|| MaterialError::RequiredPropertyNotFound("VALU".into()))?;
+
+			(
+				name.to_owned(),
+				match value {
+					FloatVal::Single(x) => MaterialPropertyValue::Float {
+						enabled: *enabled != 0,
+						value: x.to_owned()
+					},
+					FloatVal::Vector(x) => MaterialPropertyValue::Vector {
+						enabled: *enabled != 0,
+						value: x.to_owned()
+					}
+				}
+				.to_owned()
+			)
+		}
+
+		IntermediateMaterialProperty::Texture(x) => {
+			let name = x
+				.iter()
+				.find_map(|x| match x {
+					IntermediateMaterialProperty::Name(x) => Some(x),
+					_ => None
+				})
+				.ok_or_else(|| MaterialError::RequiredPropertyNotFound("NAME".into()))?;
+
+			let enabled = x
+				.iter()
+				.find_map(|x| match x {
+					IntermediateMaterialProperty::Enabled(x) => Some(x),
+					_ => None
+				})
+				.ok_or_else(|| MaterialError::RequiredPropertyNotFound("ENAB".into()))?;
+
+			let tiling_u = x
+				.iter()
+				.find_map(|x| match x {
+					IntermediateMaterialProperty::TilingU(x) => Some(x),
+					_ => None
+				})
+				.ok_or_else(|| MaterialError::RequiredPropertyNotFound("TILU".into()))?;
+
+			let tiling_v = x
+				.iter()
+				.find_map(|x| match x {
+					IntermediateMaterialProperty::TilingV(x) => Some(x),
+					_ => None
+				})
+				.ok_or_else(|| MaterialError::RequiredPropertyNotFound("TILV".into()))?;
+
+			let texture_id = x
+				.iter()
+				.find_map(|x| match x {
+					IntermediateMaterialProperty::TextureID(x) => Some(x),
+					_ => None
+				})
+				.ok_or_else(|| MaterialError::RequiredPropertyNotFound("TXID".into()))?;
+
+			let texture_type = x
+				.iter()
+				.find_map(|x| match x {
+					IntermediateMaterialProperty::Type(x) => Some(x),
+					_ => None
+				})
+				.ok_or_else(|| MaterialError::RequiredPropertyNotFound("TYPE".into()))?;
 
-					_ => return Err(MaterialError::IncorrectType(name, ty))
+			(
+				name.to_owned(),
+				MaterialPropertyValue::Texture {
+					enabled: *enabled != 0,
+					value: texture_id.to_owned(),
+					tiling_u: tiling_u.parse()?,
+					tiling_v: tiling_v.parse()?,
+					texture_type: texture_type.parse()?
 				}
-			}
+			)
 		}
 
-		// String value
-		1 => {
-			let value = String::from_utf8(
-				mati_data
-					.iter()
-					.skip(u32::from_le_bytes(data) as usize)
-					.take_while(|x| **x != 0)
-					.cloned()
-					.collect()
-			)?;
+		IntermediateMaterialProperty::Color(x) => {
+			let name = x
+				.iter()
+				.find_map(|x| match x {
+					IntermediateMaterialProperty::Name(x) => Some(x),
+					_ => None
+				})
+				.ok_or_else(|| MaterialError::RequiredPropertyNotFound("NAME".into()))?;
 
-			match name.as_ref() {
-				"BMOD" => IntermediateMaterialProperty::BlendMode(value),
-				"CULL" => IntermediateMaterialProperty::CullingMode(value),
-				"NAME" => IntermediateMaterialProperty::Name(value),
-				"TAGS" => IntermediateMaterialProperty::Tags(value),
-				"TILU" => IntermediateMaterialProperty::TilingU(value),
-				"TILV" => IntermediateMaterialProperty::TilingV(value),
-				"TYPE" => IntermediateMaterialProperty::Type(value),
+			let enabled = x
+				.iter()
+				.find_map(|x| match x {
+					IntermediateMaterialProperty::Enabled(x) => Some(x),
+					_ => None
+				})
+				.ok_or_else(|| MaterialError::RequiredPropertyNotFound("ENAB".into()))?;
 
-				_ => return Err(MaterialError::IncorrectType(name, ty))
-			}
+			let value = x
+				.iter()
+				.find_map(|x| match x {
+					IntermediateMaterialProperty::Value(x) => Some(x),
+					_ => None
+				})
+				.ok_or_else(|| MaterialError::RequiredPropertyNotFound("VALU".into()))?;
+
+			let FloatVal::Vector(value) = value else {
+				return Err(MaterialError::InvalidColor(name.to_owned()));
+			};
+
+			(
+				name.to_owned(),
+				if value.len() == 3 {
+					MaterialPropertyValue::Colour {
+						enabled: *enabled != 0,
+						value: Colour::from_components(value)?
+					}
+				} else {
+					return Err(MaterialError::InvalidColor(name.to_owned()));
+				}
+			)
 		}
 
-		// Int value
-		2 => {
-			let value = u32::from_le_bytes(data);
+		IntermediateMaterialProperty::Color4(x) => {
+			let name = x
+				.iter()
+				.find_map(|x| match x {
+					IntermediateMaterialProperty::Name(x) => Some(x),
+					_ => None
+				})
+				.ok_or_else(|| MaterialError::RequiredPropertyNotFound("NAME".into()))?;
 
-			match name.as_ref() {
-				"AREF" => IntermediateMaterialProperty::AlphaReference(value),
-				"ATST" => IntermediateMaterialProperty::AlphaTestEnabled(value),
-				"BENA" => IntermediateMaterialProperty::BlendEnabled(value),
-				"DBDE" => IntermediateMaterialProperty::DecalBlendDiffuse(value),
-				"DBEE" => IntermediateMaterialProperty::DecalBlendEmission(value),
-				"DBNE" => IntermediateMaterialProperty::DecalBlendNormal(value),
-				"DBRE" => IntermediateMaterialProperty::DecalBlendRoughness(value),
-				"DBSE" => IntermediateMaterialProperty::DecalBlendSpecular(value),
-				"ENAB" => IntermediateMaterialProperty::Enabled(value),
-				"FENA" => IntermediateMaterialProperty::FogEnabled(value),
-				"ZBIA" => IntermediateMaterialProperty::ZBias(value),
+			let enabled = x
+				.iter()
+				.find_map(|x| match x {
+					IntermediateMaterialProperty::Enabled(x) => Some(x),
+					_ => None
+				})
+				.ok_or_else(|| MaterialError::RequiredPropertyNotFound("ENAB".into()))?;
 
-				"TXID" => IntermediateMaterialProperty::TextureID(if value != 4294967295 {
-					Some(
-						mati_references
-							.get(value as usize)
-							.ok_or(MaterialError::InvalidDependency(value as usize))?
-							.resource
-							.to_owned()
-					)
+			let value = x
+				.iter()
+				.find_map(|x| match x {
+					IntermediateMaterialProperty::Value(x) => Some(x),
+					_ => None
+				})
+				.ok_or_else(|| MaterialError::RequiredPropertyNotFound("VALU".into()))?;
+
+			let FloatVal::Vector(value) = value else {
+				return Err(MaterialError::InvalidColor(name.to_owned()));
+			};
+
+			(
+				name.to_owned(),
+				if value.len() == 4 {
+					MaterialPropertyValue::Colour {
+						enabled: *enabled != 0,
+						value: Colour::from_components(value)?
+					}
 				} else {
-					None
-				}),
+					return Err(MaterialError::InvalidColor(name.to_owned()));
+				}
+			)
+		}
+
+		_ => return Err(MaterialError::PropertyAtWrongLevel(property.to_owned()))
+	}
+}
+
+#[try_fn]
+fn to_intermediate(binder: Binder) -> Result<IntermediateMaterialProperty> {
+	let mut unknown_sub_properties = binder.unknown_sub_properties;
+
+	let mut properties = [
+		vec![IntermediateMaterialProperty::RenderState({
+			let mut props = Vec::new();
+
+			// Unused by the game but added for completeness
+			props.push(IntermediateMaterialProperty::Name("RenderState".into()));
+
+			if let Some(enabled) = binder.render_state.enabled {
+				props.push(IntermediateMaterialProperty::Enabled(if enabled { 1 } else { 0 }));
+			}
+
+			if let Some(blend_enabled) = binder.render_state.blend_enabled {
+				props.push(IntermediateMaterialProperty::BlendEnabled(if blend_enabled {
+					1
+				} else {
+					0
+				}));
+			}
+
+			if let Some(blend_mode) = &binder.render_state.blend_mode {
+				props.push(IntermediateMaterialProperty::BlendMode(blend_mode.to_string()));
+			}
+
+			if let Some(decal_blend_diffuse) = binder.render_state.decal_blend_diffuse {
+				props.push(IntermediateMaterialProperty::DecalBlendDiffuse(decal_blend_diffuse));
+			}
+
+			if let Some(decal_blend_normal) = binder.render_state.decal_blend_normal {
+				props.push(IntermediateMaterialProperty::DecalBlendNormal(decal_blend_normal));
+			}
+
+			if let Some(decal_blend_specular) = binder.render_state.decal_blend_specular {
+				props.push(IntermediateMaterialProperty::DecalBlendSpecular(decal_blend_specular));
+			}
+
+			if let Some(decal_blend_roughness) = binder.render_state.decal_blend_roughness {
+				props.push(IntermediateMaterialProperty::DecalBlendRoughness(decal_blend_roughness));
+			}
+
+			if let Some(decal_blend_emission) = binder.render_state.decal_blend_emission {
+				props.push(IntermediateMaterialProperty::DecalBlendEmission(decal_blend_emission));
+			}
+
+			if let Some(alpha_test_enabled) = binder.render_state.alpha_test_enabled {
+				props.push(IntermediateMaterialProperty::AlphaTestEnabled(if alpha_test_enabled {
+					1
+				} else {
+					0
+				}));
+			}
+
+			if let Some(alpha_reference) = binder.render_state.alpha_reference {
+				props.push(IntermediateMaterialProperty::AlphaReference(alpha_reference));
+			}
+
+			if let Some(fog_enabled) = binder.render_state.fog_enabled {
+				props.push(IntermediateMaterialProperty::FogEnabled(if fog_enabled {
+					1
+				} else {
+					0
+				}));
+			}
+
+			if let Some(opacity) = binder.render_state.opacity {
+				props.push(IntermediateMaterialProperty::Opacity(opacity));
+			}
+
+			props.push(IntermediateMaterialProperty::CullingMode(
+				binder.render_state.culling_mode.to_string()
+			));
+
+			if let Some(z_bias) = binder.render_state.z_bias {
+				props.push(IntermediateMaterialProperty::ZBias(z_bias));
+			}
+
+			if let Some(z_offset) = binder.render_state.z_offset {
+				props.push(IntermediateMaterialProperty::ZOffset(z_offset));
+			}
+
+			if let Some(subsurface_red) = binder.render_state.subsurface_red {
+				props.push(IntermediateMaterialProperty::SubsurfaceRed(subsurface_red));
+			}
+
+			if let Some(subsurface_green) = binder.render_state.subsurface_green {
+				props.push(IntermediateMaterialProperty::SubsurfaceGreen(subsurface_green));
+			}
+
+			if let Some(subsurface_blue) = binder.render_state.subsurface_blue {
+				props.push(IntermediateMaterialProperty::SubsurfaceBlue(subsurface_blue));
+			}
+
+			if let Some(subsurface_value) = binder.render_state.subsurface_value {
+				props.push(IntermediateMaterialProperty::SubsurfaceValue(subsurface_value));
+			}
+
+			insert_unknown_properties(&mut props, binder.render_state.unknown_properties);
+
+			props
+		})],
+		binder
+			.properties
+			.into_iter()
+			.map(|(name, value)| {
+				let unknown = unknown_sub_properties.shift_remove(&name).unwrap_or_default();
+
+				Ok({
+					let mut property = match value {
+						MaterialPropertyValue::Float { enabled, value } => {
+							IntermediateMaterialProperty::FloatValue(vec![
+								IntermediateMaterialProperty::Name(name),
+								IntermediateMaterialProperty::Enabled(if enabled { 1 } else { 0 }),
+								IntermediateMaterialProperty::Value(FloatVal::Single(value)),
+							])
+						}
 
-				_ => return Err(MaterialError::IncorrectType(name, ty))
-			}
-		}
+						MaterialPropertyValue::Vector { enabled, value } => {
+							IntermediateMaterialProperty::FloatValue(vec![
+								IntermediateMaterialProperty::Name(name),
+								IntermediateMaterialProperty::Enabled(if enabled { 1 } else { 0 }),
+								IntermediateMaterialProperty::Value(FloatVal::Vector(value)),
+							])
+						}
 
-		// Property value
-		3 => {
-			mati.seek(SeekFrom::Start(u32::from_le_bytes(data).into()))?;
+						MaterialPropertyValue::Texture {
+							enabled,
+							value,
+							tiling_u,
+							tiling_v,
+							texture_type
+						} => IntermediateMaterialProperty::Texture(vec![
+							IntermediateMaterialProperty::Name(name),
+							IntermediateMaterialProperty::Enabled(if enabled { 1 } else { 0 }),
+							IntermediateMaterialProperty::TextureID(value),
+							IntermediateMaterialProperty::TilingU(tiling_u.to_string()),
+							IntermediateMaterialProperty::TilingV(tiling_v.to_string()),
+							IntermediateMaterialProperty::Type(texture_type.to_string()),
+						]),
+
+						MaterialPropertyValue::Colour { enabled, value } => {
+							let props = vec![
+								IntermediateMaterialProperty::Name(name),
+								IntermediateMaterialProperty::Enabled(if enabled { 1 } else { 0 }),
+								IntermediateMaterialProperty::Value(FloatVal::Vector(value.components())),
+							];
 
-			let mut values = vec![];
-			for _ in 0..count {
-				values.push(parse_material_property(mati_data, mati_references, mati.position())?);
-				mati.seek(SeekFrom::Current(0x10))?;
-			}
+							if value.a.is_some() {
+								IntermediateMaterialProperty::Color4(props)
+							} else {
+								IntermediateMaterialProperty::Color(props)
+							}
+						}
+					};
 
-			match name.as_ref() {
-				"BIND" => IntermediateMaterialProperty::Binder(values),
-				"COLO" => IntermediateMaterialProperty::Color(values),
-				"COL4" => IntermediateMaterialProperty::Color4(values),
-				"FLTV" => IntermediateMaterialProperty::FloatValue(values),
-				"INST" => IntermediateMaterialProperty::Instance(values),
-				"RSTA" => IntermediateMaterialProperty::RenderState(values),
-				"TEXT" => IntermediateMaterialProperty::Texture(values),
+					if let IntermediateMaterialProperty::FloatValue(props)
+					| IntermediateMaterialProperty::Texture(props)
+					| IntermediateMaterialProperty::Color(props)
+					| IntermediateMaterialProperty::Color4(props) = &mut property
+					{
+						insert_unknown_properties(props, unknown);
+					}
 
-				_ => return Err(MaterialError::IncorrectType(name, ty))
-			}
-		}
+					property
+				})
+			})
+			.collect::<Result<Vec<_>>>()?
+	]
+	.concat();
 
-		_ => return Err(MaterialError::UnrecognisedEntryType(ty))
-	}
+	insert_unknown_properties(&mut properties, binder.unknown_properties);
+
+	IntermediateMaterialProperty::Binder(properties)
 }
 
-#[try_fn]
-fn parse_instance(instance: IntermediateMaterialProperty) -> Result<(String, String, Binder)> {
-	let IntermediateMaterialProperty::Instance(properties) = instance else {
-		return Err(MaterialError::InstanceNotTopLevel);
-	};
+/// Collect the unknown properties within a property, recording their positions.
+fn collect_unknown_properties(properties: &[IntermediateMaterialProperty]) -> Vec<UnknownMaterialProperty> {
+	properties
+		.iter()
+		.enumerate()
+		.filter_map(|(position, x)| match x {
+			IntermediateMaterialProperty::Unknown(x) => Some(UnknownMaterialProperty {
+				position: Some(position),
+				..x.to_owned()
+			}),
+			_ => None
+		})
+		.collect()
+}
 
-	(
-		properties
-			.iter()
-			.find_map(|x| match x {
-				IntermediateMaterialProperty::Name(x) => Some(x),
-				_ => None
-			})
-			.ok_or_else(|| MaterialError::RequiredPropertyNotFound("NAME".into()))?
-			.to_owned(),
-		properties
-			.iter()
-			.find_map(|x| match x {
-				IntermediateMaterialProperty::Tags(x) => Some(x),
-				_ => None
-			})
-			.ok_or_else(|| MaterialError::RequiredPropertyNotFound("TAGS".into()))?
-			.to_owned(),
-		{
-			let binder = properties
-				.iter()
-				.find_map(|x| match x {
-					IntermediateMaterialProperty::Binder(x) => Some(x),
-					_ => None
-				})
-				.ok_or_else(|| MaterialError::RequiredPropertyNotFound("BIND".into()))?;
+/// Insert unknown properties back at their recorded positions, or at the end if they have none.
+fn insert_unknown_properties(
+	properties: &mut Vec<IntermediateMaterialProperty>,
+	mut unknown_properties: Vec<UnknownMaterialProperty>
+) {
+	// Inserting in order of position restores the original layout, as each earlier property is already in place
+	unknown_properties.sort_by_key(|x| x.position.unwrap_or(usize::MAX));
+
+	for property in unknown_properties {
+		let position = property.position.unwrap_or(usize::MAX).min(properties.len());
+		properties.insert(position, IntermediateMaterialProperty::Unknown(property));
+	}
+}
 
-			Binder {
-				render_state: {
-					let props = binder
-						.iter()
-						.find_map(|x| match x {
-							IntermediateMaterialProperty::RenderState(x) => Some(x),
-							_ => None
-						})
-						.ok_or_else(|| MaterialError::RequiredPropertyNotFound("RSTA".into()))?
-						.to_owned();
+#[cfg(test)]
+mod tests {
+	use super::*;
 
-					RenderState {
-						name: props.iter().find_map(|x| match x {
-							IntermediateMaterialProperty::Name(x) => Some(x.to_owned()),
-							_ => None
-						}),
-						enabled: props.iter().find_map(|x| match *x {
-							IntermediateMaterialProperty::Enabled(x) => Some(x != 0),
-							_ => None
-						}),
-						blend_enabled: props.iter().find_map(|x| match *x {
-							IntermediateMaterialProperty::BlendEnabled(x) => Some(x != 0),
-							_ => None
-						}),
-						blend_mode: props
-							.iter()
-							.find_map(|x| match *x {
-								IntermediateMaterialProperty::BlendMode(ref x) => Some(x.parse()),
-								_ => None
-							})
-							.transpose()?,
-						decal_blend_diffuse: props.iter().find_map(|x| match *x {
-							IntermediateMaterialProperty::DecalBlendDiffuse(x) => Some(x),
-							_ => None
-						}),
-						decal_blend_normal: props.iter().find_map(|x| match *x {
-							IntermediateMaterialProperty::DecalBlendNormal(x) => Some(x),
-							_ => None
-						}),
-						decal_blend_specular: props.iter().find_map(|x| match *x {
-							IntermediateMaterialProperty::DecalBlendSpecular(x) => Some(x),
-							_ => None
-						}),
-						decal_blend_roughness: props.iter().find_map(|x| match *x {
-							IntermediateMaterialProperty::DecalBlendRoughness(x) => Some(x),
-							_ => None
-						}),
-						decal_blend_emission: props.iter().find_map(|x| match *x {
-							IntermediateMaterialProperty::DecalBlendEmission(x) => Some(x),
-							_ => None
-						}),
-						alpha_test_enabled: props.iter().find_map(|x| match *x {
-							IntermediateMaterialProperty::AlphaTestEnabled(x) => Some(x != 0),
-							_ => None
-						}),
-						alpha_reference: props.iter().find_map(|x| match *x {
-							IntermediateMaterialProperty::AlphaReference(x) => Some(x),
-							_ => None
-						}),
-						fog_enabled: props.iter().find_map(|x| match *x {
-							IntermediateMaterialProperty::FogEnabled(x) => Some(x != 0),
-							_ => None
-						}),
-						opacity: props.iter().find_map(|x| match *x {
-							IntermediateMaterialProperty::Opacity(x) => Some(x),
-							_ => None
-						}),
-						culling_mode: props
-							.iter()
-							.find_map(|x| match *x {
-								IntermediateMaterialProperty::CullingMode(ref x) => Some(x.parse()),
-								_ => None
-							})
-							.transpose()?
-							.ok_or_else(|| MaterialError::RequiredPropertyNotFound("CULL".into()))?,
-						z_bias: props.iter().find_map(|x| match *x {
-							IntermediateMaterialProperty::ZBias(x) => Some(x),
-							_ => None
-						}),
-						z_offset: props.iter().find_map(|x| match *x {
-							IntermediateMaterialProperty::ZOffset(x) => Some(x),
-							_ => None
-						}),
-						subsurface_red: props.iter().find_map(|x| match *x {
-							IntermediateMaterialProperty::SubsurfaceRed(x) => Some(x),
-							_ => None
-						}),
-						subsurface_green: props.iter().find_map(|x| match *x {
-							IntermediateMaterialProperty::SubsurfaceGreen(x) => Some(x),
-							_ => None
-						}),
-						subsurface_blue: props.iter().find_map(|x| match *x {
-							IntermediateMaterialProperty::SubsurfaceBlue(x) => Some(x),
-							_ => None
-						}),
-						subsurface_value: props.iter().find_map(|x| match *x {
-							IntermediateMaterialProperty::SubsurfaceValue(x) => Some(x),
-							_ => None
-						})
-					}
+	fn unknown(tag: &str, data: UnknownPropertyData, position: usize) -> UnknownMaterialProperty {
+		UnknownMaterialProperty {
+			tag: tag.into(),
+			data,
+			position: Some(position)
+		}
+	}
+
+	fn material_instance() -> MaterialInstance {
+		MaterialInstance {
+			id: "00A1595C0918E2C9".parse().unwrap(),
+			name: "test".into(),
+			material_type: MaterialType::Standard,
+			tags: String::new(),
+			class: Some(ResourceReference {
+				resource: "00F5817876E691F1".parse().unwrap(),
+				flags: ReferenceFlags::default()
+			}),
+			descriptor: None,
+			impact_material: None,
+			effect_resource: None,
+			class_flags: ClassFlags::from_u32(0),
+			instance_flags: InstanceFlags::from_u32(0),
+			extra_header_words: None,
+			unknown_instance_properties: vec![unknown("XTRA", UnknownPropertyData::Int(5), 1)],
+			binder: Binder {
+				render_state: RenderState {
+					name: Some("RenderState".into()),
+					enabled: Some(true),
+					blend_enabled: None,
+					blend_mode: None,
+					decal_blend_diffuse: None,
+					decal_blend_normal: None,
+					decal_blend_specular: None,
+					decal_blend_roughness: None,
+					decal_blend_emission: None,
+					alpha_test_enabled: None,
+					alpha_reference: None,
+					fog_enabled: None,
+					opacity: None,
+					culling_mode: CullingMode::OneSided,
+					z_bias: None,
+					z_offset: None,
+					subsurface_red: None,
+					subsurface_green: None,
+					subsurface_blue: None,
+					subsurface_value: None,
+					unknown_properties: vec![unknown("RNEW", UnknownPropertyData::Float(0.5), 2)]
 				},
+				properties: IndexMap::from([
+					(
+						"mapDiffuse".into(),
+						MaterialPropertyValue::Texture {
+							enabled: true,
+							value: Some("00C2D5D1D40D9B6B".parse().unwrap()),
+							tiling_u: TilingMode::Tiled,
+							tiling_v: TilingMode::Tiled,
+							texture_type: TextureSlotType::Colour
+						}
+					),
+					(
+						"vEmissive".into(),
+						MaterialPropertyValue::Colour {
+							enabled: true,
+							value: Colour::from_components(&[0.25, 0.5, 1.5]).unwrap()
+						}
+					),
+					(
+						"fGlossiness".into(),
+						MaterialPropertyValue::Float {
+							enabled: false,
+							value: 0.1
+						}
+					)
+				]),
+				unknown_properties: vec![unknown("BNEW", UnknownPropertyData::String("new".into()), 2)],
+				unknown_sub_properties: IndexMap::from([(
+					"mapDiffuse".into(),
+					vec![unknown("TNEW", UnknownPropertyData::Vector(vec![1.0, 2.0]), 3)]
+				)])
+			}
+		}
+	}
 
-				properties: binder
-					.iter()
-					.filter(|x| !matches!(x, IntermediateMaterialProperty::RenderState(_)))
-					.map(|x| {
-						Ok(match x {
-							IntermediateMaterialProperty::FloatValue(x) => {
-								let name = x
-									.iter()
-									.find_map(|x| match x {
-										IntermediateMaterialProperty::Name(x) => Some(x),
-										_ => None
-									})
-									.ok_or_else(|| MaterialError::RequiredPropertyNotFound("NAME".into()))?;
-
-								let enabled = x
-									.iter()
-									.find_map(|x| match x {
-										IntermediateMaterialProperty::Enabled(x) => Some(x),
-										_ => None
-									})
-									.ok_or_else(|| MaterialError::RequiredPropertyNotFound("ENAB".into()))?;
-
-								let value = x
-									.iter()
-									.find_map(|x| match x {
-										IntermediateMaterialProperty::Value(x) => Some(x),
-										_ => None
-									})
-									.ok_or_else(|| MaterialError::RequiredPropertyNotFound("VALU".into()))?;
-
-								(
-									name.to_owned(),
-									match value {
-										FloatVal::Single(x) => MaterialPropertyValue::Float {
-											enabled: *enabled != 0,
-											value: x.to_owned()
-										},
-										FloatVal::Vector(x) => MaterialPropertyValue::Vector {
-											enabled: *enabled != 0,
-											value: x.to_owned()
-										}
-									}
-									.to_owned()
-								)
-							}
+	#[test]
+	fn unknown_properties_round_trip() {
+		let (mati, metadata) = material_instance().generate().unwrap();
 
-							IntermediateMaterialProperty::Texture(x) => {
-								let name = x
-									.iter()
-									.find_map(|x| match x {
-										IntermediateMaterialProperty::Name(x) => Some(x),
-										_ => None
-									})
-									.ok_or_else(|| MaterialError::RequiredPropertyNotFound("NAME".into()))?;
-
-								let enabled = x
-									.iter()
-									.find_map(|x| match x {
-										IntermediateMaterialProperty::Enabled(x) => Some(x),
-										_ => None
-									})
-									.ok_or_else(|| MaterialError::RequiredPropertyNotFound("ENAB".into()))?;
-
-								let tiling_u = x
-									.iter()
-									.find_map(|x| match x {
-										IntermediateMaterialProperty::TilingU(x) => Some(x),
-										_ => None
-									})
-									.ok_or_else(|| MaterialError::RequiredPropertyNotFound("TILU".into()))?;
-
-								let tiling_v = x
-									.iter()
-									.find_map(|x| match x {
-										IntermediateMaterialProperty::TilingV(x) => Some(x),
-										_ => None
-									})
-									.ok_or_else(|| MaterialError::RequiredPropertyNotFound("TILV".into()))?;
-
-								let texture_id = x
-									.iter()
-									.find_map(|x| match x {
-										IntermediateMaterialProperty::TextureID(x) => Some(x),
-										_ => None
-									})
-									.ok_or_else(|| MaterialError::RequiredPropertyNotFound("TXID".into()))?;
-
-								let texture_type = x
-									.iter()
-									.find_map(|x| match x {
-										IntermediateMaterialProperty::Type(x) => Some(x),
-										_ => None
-									})
-									.ok_or_else(|| MaterialError::RequiredPropertyNotFound("TYPE".into()))?;
-
-								(
-									name.to_owned(),
-									MaterialPropertyValue::Texture {
-										enabled: *enabled != 0,
-										value: texture_id.to_owned(),
-										tiling_u: tiling_u.to_owned(),
-										tiling_v: tiling_v.to_owned(),
-										texture_type: texture_type.to_owned()
-									}
-								)
-							}
+		let parsed = MaterialInstance::parse(&mati, &metadata).unwrap();
+		assert_eq!(parsed, material_instance());
 
-							IntermediateMaterialProperty::Color(x) => {
-								let name = x
-									.iter()
-									.find_map(|x| match x {
-										IntermediateMaterialProperty::Name(x) => Some(x),
-										_ => None
-									})
-									.ok_or_else(|| MaterialError::RequiredPropertyNotFound("NAME".into()))?;
-
-								let enabled = x
-									.iter()
-									.find_map(|x| match x {
-										IntermediateMaterialProperty::Enabled(x) => Some(x),
-										_ => None
-									})
-									.ok_or_else(|| MaterialError::RequiredPropertyNotFound("ENAB".into()))?;
-
-								let value = x
-									.iter()
-									.find_map(|x| match x {
-										IntermediateMaterialProperty::Value(x) => Some(x),
-										_ => None
-									})
-									.ok_or_else(|| MaterialError::RequiredPropertyNotFound("VALU".into()))?;
-
-								let FloatVal::Vector(value) = value else {
-									return Err(MaterialError::InvalidColor(name.to_owned()));
-								};
-
-								(
-									name.to_owned(),
-									if value.len() == 3 {
-										MaterialPropertyValue::Colour {
-											enabled: *enabled != 0,
-											value: format!(
-												"#{:0>2x}{:0>2x}{:0>2x}",
-												(value[0] * 255.0).round() as u8,
-												(value[1] * 255.0).round() as u8,
-												(value[2] * 255.0).round() as u8
-											)
-										}
-									} else {
-										return Err(MaterialError::InvalidColor(name.to_owned()));
-									}
-								)
-							}
+		let (regenerated, _) = parsed.generate().unwrap();
+		assert_eq!(regenerated, mati);
+	}
 
-							IntermediateMaterialProperty::Color4(x) => {
-								let name = x
-									.iter()
-									.find_map(|x| match x {
-										IntermediateMaterialProperty::Name(x) => Some(x),
-										_ => None
-									})
-									.ok_or_else(|| MaterialError::RequiredPropertyNotFound("NAME".into()))?;
-
-								let enabled = x
-									.iter()
-									.find_map(|x| match x {
-										IntermediateMaterialProperty::Enabled(x) => Some(x),
-										_ => None
-									})
-									.ok_or_else(|| MaterialError::RequiredPropertyNotFound("ENAB".into()))?;
-
-								let value = x
-									.iter()
-									.find_map(|x| match x {
-										IntermediateMaterialProperty::Value(x) => Some(x),
-										_ => None
-									})
-									.ok_or_else(|| MaterialError::RequiredPropertyNotFound("VALU".into()))?;
-
-								let FloatVal::Vector(value) = value else {
-									return Err(MaterialError::InvalidColor(name.to_owned()));
-								};
-
-								(
-									name.to_owned(),
-									if value.len() == 4 {
-										MaterialPropertyValue::Colour {
-											enabled: *enabled != 0,
-											value: format!(
-												"#{:0>2x}{:0>2x}{:0>2x}{:0>2x}",
-												(value[0] * 255.0).round() as u8,
-												(value[1] * 255.0).round() as u8,
-												(value[2] * 255.0).round() as u8,
-												(value[3] * 255.0).round() as u8
-											)
-										}
-									} else {
-										return Err(MaterialError::InvalidColor(name.to_owned()));
-									}
-								)
-							}
+	#[test]
+	fn header_round_trip() {
+		let mut instance = material_instance();
 
-							_ => return Err(MaterialError::PropertyAtWrongLevel(x.to_owned()))
-						})
-					})
-					.collect::<Result<_>>()?
+		instance.descriptor = Some(ResourceReference {
+			resource: "00E2A1B3C4D5E6F7".parse().unwrap(),
+			flags: ReferenceFlags {
+				reference_type: ReferenceType::Install,
+				acquired: true,
+				language_code: 2
 			}
-		}
-	)
-}
+		});
 
-#[try_fn]
-fn to_intermediate(binder: Binder) -> Result<IntermediateMaterialProperty> {
-	IntermediateMaterialProperty::Binder(
-		[
-			vec![IntermediateMaterialProperty::RenderState({
-				let mut props = Vec::new();
+		// Same resource as the diffuse texture, kept as its own reference
+		instance.impact_material = Some(ResourceReference {
+			resource: "00C2D5D1D40D9B6B".parse().unwrap(),
+			flags: ReferenceFlags {
+				reference_type: ReferenceType::Normal,
+				acquired: false,
+				language_code: 0b0001_1111
+			}
+		});
 
-				// Unused by the game but added for completeness
-				props.push(IntermediateMaterialProperty::Name("RenderState".into()));
+		instance.effect_resource = Some(ResourceReference {
+			resource: "00D2C3A6BCFB9C1B".parse().unwrap(),
+			flags: ReferenceFlags {
+				reference_type: ReferenceType::Weak,
+				acquired: true,
+				language_code: 0
+			}
+		});
 
-				if let Some(enabled) = binder.render_state.enabled {
-					props.push(IntermediateMaterialProperty::Enabled(if enabled { 1 } else { 0 }));
-				}
+		instance.extra_header_words = Some(vec![3, 0, 0, 1]);
 
-				if let Some(blend_enabled) = binder.render_state.blend_enabled {
-					props.push(IntermediateMaterialProperty::BlendEnabled(if blend_enabled {
-						1
-					} else {
-						0
-					}));
-				}
+		let (mati, metadata) = instance.clone().generate().unwrap();
 
-				if let Some(blend_mode) = &binder.render_state.blend_mode {
-					props.push(IntermediateMaterialProperty::BlendMode(blend_mode.to_string()));
-				}
+		assert_eq!(
+			metadata
+				.references
+				.iter()
+				.filter(|x| x.resource == "00C2D5D1D40D9B6B".parse().unwrap())
+				.count(),
+			2
+		);
 
-				if let Some(decal_blend_diffuse) = binder.render_state.decal_blend_diffuse {
-					props.push(IntermediateMaterialProperty::DecalBlendDiffuse(decal_blend_diffuse));
-				}
+		let parsed = MaterialInstance::parse(&mati, &metadata).unwrap();
+		assert_eq!(parsed, instance);
 
-				if let Some(decal_blend_normal) = binder.render_state.decal_blend_normal {
-					props.push(IntermediateMaterialProperty::DecalBlendNormal(decal_blend_normal));
-				}
+		let (regenerated, regenerated_metadata) = parsed.generate().unwrap();
+		assert_eq!(regenerated, mati);
+		assert_eq!(regenerated_metadata.references, metadata.references);
+	}
 
-				if let Some(decal_blend_specular) = binder.render_state.decal_blend_specular {
-					props.push(IntermediateMaterialProperty::DecalBlendSpecular(decal_blend_specular));
-				}
+	#[test]
+	fn default_header_words() {
+		let (mati, _) = material_instance().generate().unwrap();
 
-				if let Some(decal_blend_roughness) = binder.render_state.decal_blend_roughness {
-					props.push(IntermediateMaterialProperty::DecalBlendRoughness(decal_blend_roughness));
-				}
+		assert_eq!(
+			mati[mati.len() - 16..],
+			[3, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+		);
+	}
 
-				if let Some(decal_blend_emission) = binder.render_state.decal_blend_emission {
-					props.push(IntermediateMaterialProperty::DecalBlendEmission(decal_blend_emission));
-				}
+	#[test]
+	fn validate_reports_unknown_texture_parameters() {
+		let class = MaterialClass {
+			id: "00F5817876E691F1".parse().unwrap(),
+			name: "test".into(),
+			tags: String::new(),
+			unknown_instance_properties: vec![],
+			binder: material_instance().binder
+		};
 
-				if let Some(alpha_test_enabled) = binder.render_state.alpha_test_enabled {
-					props.push(IntermediateMaterialProperty::AlphaTestEnabled(if alpha_test_enabled {
-						1
-					} else {
-						0
-					}));
-				}
+		let mut instance = material_instance();
 
-				if let Some(alpha_reference) = binder.render_state.alpha_reference {
-					props.push(IntermediateMaterialProperty::AlphaReference(alpha_reference));
+		instance.binder.properties.insert(
+			"mapDiffuse".into(),
+			MaterialPropertyValue::Texture {
+				enabled: true,
+				value: None,
+				tiling_u: "TILED ".parse().unwrap(),
+				tiling_v: TilingMode::Tiled,
+				texture_type: "Colour".parse().unwrap()
+			}
+		);
+
+		assert_eq!(
+			instance.validate(&class),
+			vec![
+				MaterialValidationIssue::UnknownTilingMode {
+					property: "mapDiffuse".into(),
+					value: "TILED ".into()
+				},
+				MaterialValidationIssue::UnknownTextureSlotType {
+					property: "mapDiffuse".into(),
+					value: "Colour".into()
+				},
+				MaterialValidationIssue::TextureTypeMismatch {
+					property: "mapDiffuse".into(),
+					expected: TextureSlotType::Colour,
+					found: TextureSlotType::Other("Colour".into())
 				}
+			]
+		);
+	}
 
-				if let Some(fog_enabled) = binder.render_state.fog_enabled {
-					props.push(IntermediateMaterialProperty::FogEnabled(if fog_enabled {
-						1
-					} else {
-						0
-					}));
-				}
+	#[test]
+	fn gltf_maps_textures_by_slot_type() {
+		let mut instance = material_instance();
+
+		instance.binder.properties.insert(
+			"mapGlow".into(),
+			MaterialPropertyValue::Texture {
+				enabled: true,
+				value: Some("00D2C3A6BCFB9C1B".parse().unwrap()),
+				tiling_u: TilingMode::Tiled,
+				tiling_v: TilingMode::Tiled,
+				texture_type: TextureSlotType::Emission
+			}
+		);
 
-				if let Some(opacity) = binder.render_state.opacity {
-					props.push(IntermediateMaterialProperty::Opacity(opacity));
-				}
+		instance.binder.properties.shift_remove("vEmissive");
 
-				props.push(IntermediateMaterialProperty::CullingMode(
-					binder.render_state.culling_mode.to_string()
-				));
+		let export = instance.to_gltf(|id| Some(format!("{id}.png")));
 
-				if let Some(z_bias) = binder.render_state.z_bias {
-					props.push(IntermediateMaterialProperty::ZBias(z_bias));
-				}
+		assert_eq!(
+			export.material.pbr_metallic_roughness.base_color_texture,
+			Some(GltfTextureInfo { index: 0 })
+		);
+		assert_eq!(export.material.emissive_texture, Some(GltfTextureInfo { index: 1 }));
+		assert_eq!(export.material.emissive_factor, [1.0, 1.0, 1.0]);
+		assert_eq!(export.unmapped, vec!["fGlossiness".to_owned()]);
+	}
 
-				if let Some(z_offset) = binder.render_state.z_offset {
-					props.push(IntermediateMaterialProperty::ZOffset(z_offset));
-				}
+	#[test]
+	fn resolve_reports_mismatched_material() {
+		let instance = material_instance();
 
-				if let Some(subsurface_red) = binder.render_state.subsurface_red {
-					props.push(IntermediateMaterialProperty::SubsurfaceRed(subsurface_red));
-				}
+		let entity = MaterialEntity {
+			factory: "00B0D6D2D8DB3C27".parse().unwrap(),
+			blueprint: "0085A8F1B3E1E5A3".parse().unwrap(),
+			material: "00F5817876E691F1".parse().unwrap(),
+			overrides: IndexMap::from([("fGlossiness".into(), MaterialOverride::Float(0.5))])
+		};
 
-				if let Some(subsurface_green) = binder.render_state.subsurface_green {
-					props.push(IntermediateMaterialProperty::SubsurfaceGreen(subsurface_green));
-				}
+		let (resolved, issues) = instance.resolve(&entity);
+
+		assert_eq!(
+			issues,
+			vec![MaterialValidationIssue::MaterialMismatch {
+				expected: instance.id,
+				found: entity.material
+			}]
+		);
+		assert_eq!(
+			resolved.binder.properties.get("fGlossiness"),
+			Some(&MaterialPropertyValue::Float {
+				enabled: false,
+				value: 0.5
+			})
+		);
+	}
 
-				if let Some(subsurface_blue) = binder.render_state.subsurface_blue {
-					props.push(IntermediateMaterialProperty::SubsurfaceBlue(subsurface_blue));
-				}
+	#[test]
+	fn colour_round_trip() {
+		let rgb = Colour::from_components(&[1.0, 0.0, 51.0 / 255.0]).unwrap();
+		let rgba = Colour::from_components(&[0.0, 128.0 / 255.0, 1.0, 64.0 / 255.0]).unwrap();
+		let hdr = Colour::from_components(&[2.5, 0.1, 0.0]).unwrap();
 
-				if let Some(subsurface_value) = binder.render_state.subsurface_value {
-					props.push(IntermediateMaterialProperty::SubsurfaceValue(subsurface_value));
-				}
+		assert_eq!(rgb.to_hex().as_deref(), Some("#ff0033"));
+		assert_eq!(rgba.to_hex().as_deref(), Some("#0080ff40"));
+		assert_eq!(hdr.to_hex(), None);
 
-				props
-			})],
-			binder
-				.properties
-				.into_iter()
-				.map(|(name, value)| {
-					Ok({
-						match value {
-							MaterialPropertyValue::Float { enabled, value } => {
-								IntermediateMaterialProperty::FloatValue(vec![
-									IntermediateMaterialProperty::Name(name),
-									IntermediateMaterialProperty::Enabled(if enabled { 1 } else { 0 }),
-									IntermediateMaterialProperty::Value(FloatVal::Single(value)),
-								])
-							}
+		for colour in [rgb, rgba, hdr] {
+			assert_eq!(Colour::from_components(&colour.components()).unwrap(), colour);
 
-							MaterialPropertyValue::Vector { enabled, value } => {
-								IntermediateMaterialProperty::FloatValue(vec![
-									IntermediateMaterialProperty::Name(name),
-									IntermediateMaterialProperty::Enabled(if enabled { 1 } else { 0 }),
-									IntermediateMaterialProperty::Value(FloatVal::Vector(value)),
-								])
-							}
+			if let Some(hex) = colour.to_hex() {
+				assert_eq!(hex.parse::<Colour>().unwrap(), colour);
+			}
+		}
 
-							MaterialPropertyValue::Texture {
-								enabled,
-								value,
-								tiling_u,
-								tiling_v,
-								texture_type
-							} => IntermediateMaterialProperty::Texture(vec![
-								IntermediateMaterialProperty::Name(name),
-								IntermediateMaterialProperty::Enabled(if enabled { 1 } else { 0 }),
-								IntermediateMaterialProperty::TextureID(value),
-								IntermediateMaterialProperty::TilingU(tiling_u),
-								IntermediateMaterialProperty::TilingV(tiling_v),
-								IntermediateMaterialProperty::Type(texture_type),
-							]),
-
-							MaterialPropertyValue::Colour { enabled, value } => {
-								if value.len() > 7 {
-									let r = u8::from_str_radix(&value.chars().skip(1).take(2).collect::<String>(), 16)?
-										as f32 / 255.0;
-
-									let g = u8::from_str_radix(&value.chars().skip(3).take(2).collect::<String>(), 16)?
-										as f32 / 255.0;
-
-									let b = u8::from_str_radix(&value.chars().skip(5).take(2).collect::<String>(), 16)?
-										as f32 / 255.0;
-
-									let a = u8::from_str_radix(&value.chars().skip(7).take(2).collect::<String>(), 16)?
-										as f32 / 255.0;
-
-									IntermediateMaterialProperty::Color4(vec![
-										IntermediateMaterialProperty::Name(name),
-										IntermediateMaterialProperty::Enabled(if enabled { 1 } else { 0 }),
-										IntermediateMaterialProperty::Value(FloatVal::Vector(vec![r, g, b, a])),
-									])
-								} else {
-									let r = u8::from_str_radix(&value.chars().skip(1).take(2).collect::<String>(), 16)?
-										as f32 / 255.0;
-
-									let g = u8::from_str_radix(&value.chars().skip(3).take(2).collect::<String>(), 16)?
-										as f32 / 255.0;
-
-									let b = u8::from_str_radix(&value.chars().skip(5).take(2).collect::<String>(), 16)?
-										as f32 / 255.0;
-
-									IntermediateMaterialProperty::Color(vec![
-										IntermediateMaterialProperty::Name(name),
-										IntermediateMaterialProperty::Enabled(if enabled { 1 } else { 0 }),
-										IntermediateMaterialProperty::Value(FloatVal::Vector(vec![r, g, b])),
-									])
-								}
-							}
-						}
-					})
-				})
-				.collect::<Result<Vec<_>>>()?
-		]
-		.concat()
-	)
+		assert!(Colour::from_components(&[1.0, 0.0]).is_err());
+		assert!("#ff00".parse::<Colour>().is_err());
+	}
+
+	#[cfg(feature = "serde")]
+	#[test]
+	fn colour_serde_round_trip() {
+		for colour in [
+			Colour::from_components(&[1.0, 0.0, 51.0 / 255.0]).unwrap(),
+			Colour::from_components(&[2.5, 0.1, 0.0, 1.0]).unwrap()
+		] {
+			let json = serde_json::to_string(&colour).unwrap();
+			assert_eq!(serde_json::from_str::<Colour>(&json).unwrap(), colour);
+		}
+	}
+
+	#[cfg(feature = "serde")]
+	#[test]
+	fn colour_property_json_round_trip() {
+		let mut instance = material_instance();
+
+		instance.binder.properties.insert(
+			"cDiffuse".into(),
+			MaterialPropertyValue::Colour {
+				enabled: true,
+				value: Colour::from_components(&[1.0, 0.0, 51.0 / 255.0]).unwrap()
+			}
+		);
+
+		instance.binder.properties.insert(
+			"cTint".into(),
+			MaterialPropertyValue::Colour {
+				enabled: false,
+				value: Colour::from_components(&[0.0, 128.0 / 255.0, 1.0, 64.0 / 255.0]).unwrap()
+			}
+		);
+
+		instance.binder.properties.insert(
+			"vOffset".into(),
+			MaterialPropertyValue::Vector {
+				enabled: true,
+				value: vec![0.25, 0.5, 1.5]
+			}
+		);
+
+		let json = serde_json::to_value(&instance).unwrap();
+		let properties = &json["properties"];
+
+		assert_eq!(
+			properties["cDiffuse"],
+			serde_json::json!({ "enabled": true, "value": "#ff0033" })
+		);
+		assert_eq!(
+			properties["cTint"],
+			serde_json::json!({ "enabled": false, "value": "#0080ff40" })
+		);
+		assert_eq!(
+			properties["vEmissive"],
+			serde_json::json!({ "enabled": true, "value": { "r": 0.25, "g": 0.5, "b": 1.5 } })
+		);
+
+		assert_eq!(serde_json::from_value::<MaterialInstance>(json).unwrap(), instance);
+	}
 }
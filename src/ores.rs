@@ -1,25 +1,47 @@
-use std::io::{Cursor, Read, Seek, SeekFrom, Write};
-
 use glacier_commons::metadata::{FromU64Error, RuntimeID};
 use indexmap::IndexMap;
 use thiserror::Error;
 use tryvial::try_fn;
 
+use crate::bin1::{Bin1, Bin1Error, Bin1Writer};
+
 #[cfg(feature = "rune")]
 pub fn rune_module() -> Result<rune::Module, rune::ContextError> {
 	let mut module = rune::Module::with_crate_item("glacier_formats", ["ores"])?;
 
 	module.ty::<OresError>()?;
+	module.ty::<HashList>()?;
 	module.function_meta(r_parse_hashes_ores)?;
 	module.function_meta(r_serialise_hashes_ores)?;
+	module.ty::<HashMismatch>()?;
+	module.function_meta(hash_path__meta)?;
+	module.function_meta(r_verify_hashes)?;
 	module.function_meta(parse_json_ores__meta)?;
 	module.function_meta(serialise_json_ores__meta)?;
+	module.function_meta(parse_string_array_ores__meta)?;
+	module.function_meta(r_serialise_string_array_ores)?;
+	module.function_meta(parse_blob_list_ores__meta)?;
+	module.function_meta(r_serialise_blob_list_ores)?;
+	#[cfg(feature = "serde")]
+	module.function_meta(r_merge_json_ores)?;
+	module.ty::<OresKind>()?;
+	module.function_meta(OresKind::detect__meta)?;
+	module.ty::<Ores>()?;
 
 	Ok(module)
 }
 
 type Result<T, E = OresError> = std::result::Result<T, E>;
 
+/// The size of an entry in a hashes ORES: a string header followed by the hash.
+const HASHES_ENTRY_SIZE: u64 = 0x18;
+
+/// The size of a string header.
+const STRING_SIZE: u64 = 0x10;
+
+/// The size of an array header, as used for each blob in a blob list ORES.
+const ARRAY_SIZE: u64 = 0x18;
+
 #[derive(Error, Debug)]
 #[cfg_attr(feature = "rune", derive(better_rune_derive::Any))]
 #[cfg_attr(feature = "rune", rune(item = ::glacier_formats::ores))]
@@ -34,207 +56,798 @@ pub enum OresError {
 	#[error("invalid UTF-8: {0}")]
 	InvalidString(#[from] std::string::FromUtf8Error),
 
+	#[error("invalid BIN1: {0}")]
+	Bin1(Bin1Error),
+
+	#[error("not a BIN1 file")]
+	BadMagic,
+
+	#[error("offset {0:#x} is outside the data section")]
+	OffsetOutOfRange(u64),
+
+	#[error("array at {offset:#x} claims {claimed} entries but has room for {actual}")]
+	InconsistentEntryCount { offset: u64, claimed: u64, actual: u64 },
+
+	#[error("string at {0:#x} has a size that doesn't match its length")]
+	InconsistentStringLength(u64),
+
 	#[error("hashes ORES must have data")]
 	ValuesEmpty,
 
 	#[error("invalid RuntimeID: {0}")]
-	InvalidRuntimeID(#[from] FromU64Error)
+	InvalidRuntimeID(#[from] FromU64Error),
+
+	#[error("unrecognised ORES layout")]
+	UnknownKind,
+
+	#[cfg(feature = "serde")]
+	#[error("invalid JSON: {0}")]
+	InvalidJson(#[from] serde_json::Error)
+}
+
+impl From<Bin1Error> for OresError {
+	fn from(value: Bin1Error) -> Self {
+		match value {
+			Bin1Error::InvalidNumber(err) => Self::InvalidNumber(err),
+			Bin1Error::InvalidString(err) => Self::InvalidString(err),
+			Bin1Error::BadMagic => Self::BadMagic,
+			Bin1Error::OffsetOutOfRange(offset) => Self::OffsetOutOfRange(offset),
+			_ => Self::Bin1(value)
+		}
+	}
+}
+
+/// Read an array, checking that the count stored before its elements agrees with its size.
+#[try_fn]
+fn read_array(bin1: &Bin1, offset: u64, element_size: u64) -> Result<Vec<u64>> {
+	let entries = bin1.read_array(offset, element_size)?;
+
+	if let Some(&begin) = entries.first() {
+		let claimed = u64::from(bin1.read_u32(begin.checked_sub(4).ok_or(OresError::OffsetOutOfRange(begin))?)?);
+
+		if claimed != entries.len() as u64 {
+			return Err(OresError::InconsistentEntryCount {
+				offset,
+				claimed,
+				actual: entries.len() as u64
+			});
+		}
+	}
+
+	entries
+}
+
+/// Read a string, checking that the size stored before its characters agrees with its length and that it is
+/// null-terminated.
+#[try_fn]
+fn read_string(bin1: &Bin1, offset: u64) -> Result<String> {
+	let value = bin1.read_string(offset)?;
+	let pointer = bin1.read_pointer(offset + 8)?;
+
+	let size = bin1.read_u32(pointer.checked_sub(4).ok_or(OresError::OffsetOutOfRange(pointer))?)?;
+
+	if size == 0
+		|| u64::from(size) != value.len() as u64 + 1
+		|| bin1.read_bytes(pointer + value.len() as u64, 1)? != [0]
+	{
+		return Err(OresError::InconsistentStringLength(offset));
+	}
+
+	value
 }
 
+#[cfg(feature = "rune")]
+crate::rune_map::ordered_map!(
+	/// A hash list, mapping RuntimeIDs to their paths in order.
+	HashList,
+	RuntimeID,
+	String,
+	::glacier_formats::ores
+);
+
 #[cfg(feature = "rune")]
 #[rune::function(path = parse_hashes_ores)]
 #[try_fn]
-fn r_parse_hashes_ores(bin_data: &[u8]) -> Result<Vec<(RuntimeID, String)>> {
-	parse_hashes_ores(bin_data)?.into_iter().collect()
+fn r_parse_hashes_ores(bin_data: &[u8]) -> Result<HashList> {
+	HashList(parse_hashes_ores(bin_data)?)
 }
 
 #[try_fn]
 #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
 pub fn parse_hashes_ores(bin_data: &[u8]) -> Result<IndexMap<RuntimeID, String>> {
+	let bin1 = Bin1::parse(bin_data)?;
+
 	let mut data = IndexMap::new();
 
-	let mut cursor = Cursor::new(bin_data);
+	for entry in read_array(&bin1, 0, HASHES_ENTRY_SIZE)? {
+		let value = read_string(&bin1, entry)?;
 
-	cursor.seek(SeekFrom::Start(8))?;
+		// The hash is stored as its high half followed by its low half
+		let hash =
+			((u64::from(bin1.read_u32(entry + 16)?) << 32) | u64::from(bin1.read_u32(entry + 20)?)).try_into()?;
 
-	let end_of_strings = i32::from_be_bytes({
-		let mut x = [0u8; 4];
-		cursor.read_exact(&mut x)?;
-		x
-	});
+		data.insert(hash, value);
+	}
 
-	cursor.seek(SeekFrom::Start(u64::try_from(end_of_strings)? + 24))?;
+	data
+}
 
-	let number_of_entries = i32::from_le_bytes({
-		let mut x = [0u8; 4];
-		cursor.read_exact(&mut x)?;
-		x
-	});
+#[cfg(feature = "rune")]
+#[rune::function(path = serialise_hashes_ores)]
+fn r_serialise_hashes_ores(data: HashList) -> Result<Vec<u8>> {
+	serialise_hashes_ores(&data.0)
+}
 
-	let mut offsets = Vec::new();
-	for _ in 0..number_of_entries {
-		offsets.push(i32::from_le_bytes({
-			let mut x = [0u8; 4];
-			cursor.read_exact(&mut x)?;
-			x
-		}));
+#[try_fn]
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+pub fn serialise_hashes_ores(data: &IndexMap<RuntimeID, String>) -> Result<Vec<u8>> {
+	if data.is_empty() {
+		return Err(OresError::ValuesEmpty);
 	}
 
-	for i in 3..number_of_entries {
-		let i = usize::try_from(i)?;
+	let mut writer = Bin1Writer::new();
 
-		cursor.seek(SeekFrom::Start(u64::try_from(offsets[i] + 16)?))?;
+	let entries_start = writer.write_array_header(data.len(), HASHES_ENTRY_SIZE as usize)?;
 
-		let offset_of_data = i32::from_le_bytes({
-			let mut x = [0u8; 4];
-			cursor.read_exact(&mut x)?;
-			x
-		});
+	// Strings are laid out after the entries, each aligned to 4 bytes and preceded by its size
+	let mut string_offset = entries_start + HASHES_ENTRY_SIZE * data.len() as u64;
+	for (hash, value) in data {
+		writer.write_string_header(value.len(), string_offset + 4)?;
+		writer.write_u32((hash.as_u64() >> 32) as u32);
+		writer.write_u32(hash.as_u64() as u32);
 
-		cursor.seek(SeekFrom::Current(4))?;
+		string_offset = (string_offset + 4 + value.len() as u64 + 1).next_multiple_of(4);
+	}
 
-		let hash_bytes = {
-			let mut x = [0u8; 8];
-			cursor.read_exact(&mut x)?;
-			x
-		};
+	for (i, value) in data.values().enumerate() {
+		writer.write_string_data(value)?;
 
-		let hash = u64::from_be_bytes([
-			hash_bytes[3],
-			hash_bytes[2],
-			hash_bytes[1],
-			hash_bytes[0],
-			hash_bytes[7],
-			hash_bytes[6],
-			hash_bytes[5],
-			hash_bytes[4]
-		])
-		.try_into()?;
+		if i != data.len() - 1 {
+			writer.align(4);
+		}
+	}
 
-		cursor.seek(SeekFrom::Start(u64::try_from(offset_of_data + 12)?))?;
+	writer.finish(8).generate()?
+}
 
-		let len = i32::from_le_bytes({
-			let mut x = [0u8; 4];
-			cursor.read_exact(&mut x)?;
-			x
-		});
+/// Compute the RuntimeID of a resource path: the low 56 bits of the first 8 bytes of the MD5 of the lowercased path,
+/// read as big-endian (i.e. bytes 1 to 7 of the digest).
+#[cfg_attr(feature = "rune", rune::function(keep))]
+pub fn hash_path(path: &str) -> RuntimeID {
+	let digest = md5::compute(path.to_lowercase());
 
-		let str_bytes = {
-			let mut x = vec![0u8; usize::try_from(len)? - 1];
-			cursor.read_exact(&mut x)?;
-			x
-		};
+	(u64::from_be_bytes(digest[0..8].try_into().unwrap()) & 0x00FF_FFFF_FFFF_FFFF)
+		.try_into()
+		.unwrap()
+}
 
-		data.insert(hash, String::from_utf8(str_bytes)?);
-	}
+/// An entry in a hash list whose RuntimeID doesn't match the hash of its path.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "rune", serde_with::apply(_ => #[rune(get, set)]))]
+#[cfg_attr(feature = "rune", derive(better_rune_derive::Any))]
+#[cfg_attr(feature = "rune", rune(item = ::glacier_formats::ores))]
+#[cfg_attr(feature = "rune", rune_derive(DEBUG_FMT, CLONE, PARTIAL_EQ, EQ))]
+pub struct HashMismatch {
+	pub id: RuntimeID,
+	pub path: String,
 
-	data
+	/// The RuntimeID the path actually hashes to.
+	pub expected: RuntimeID
 }
 
 #[cfg(feature = "rune")]
-#[rune::function(path = serialise_hashes_ores)]
-fn r_serialise_hashes_ores(data: Vec<(RuntimeID, String)>) -> Result<Vec<u8>> {
-	serialise_hashes_ores(&data.into_iter().collect())
+#[rune::function(path = verify_hashes)]
+fn r_verify_hashes(data: HashList) -> Vec<HashMismatch> {
+	verify_hashes(&data.0)
+}
+
+/// Check that every entry in a hash list has the RuntimeID of its path.
+pub fn verify_hashes(data: &IndexMap<RuntimeID, String>) -> Vec<HashMismatch> {
+	data.iter()
+		.filter_map(|(&id, path)| {
+			let expected = hash_path(path);
+
+			(expected != id).then(|| HashMismatch {
+				id,
+				path: path.to_owned(),
+				expected
+			})
+		})
+		.collect()
+}
+
+/// Two hash lists disagreeing on the path of a RuntimeID.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HashConflict {
+	pub id: RuntimeID,
+	pub existing: String,
+	pub incoming: String
+}
+
+/// Merge several hash lists. Earlier lists take precedence; any disagreement is reported as a conflict.
+pub fn merge_hashes<'a>(
+	lists: impl IntoIterator<Item = &'a IndexMap<RuntimeID, String>>
+) -> (IndexMap<RuntimeID, String>, Vec<HashConflict>) {
+	let mut merged: IndexMap<RuntimeID, String> = IndexMap::new();
+	let mut conflicts = vec![];
+
+	for list in lists {
+		for (&id, path) in list {
+			match merged.get(&id) {
+				Some(existing) if existing != path => conflicts.push(HashConflict {
+					id,
+					existing: existing.to_owned(),
+					incoming: path.to_owned()
+				}),
+
+				Some(_) => {}
+
+				None => {
+					merged.insert(id, path.to_owned());
+				}
+			}
+		}
+	}
+
+	(merged, conflicts)
+}
+
+/// The differences between two hash lists.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HashesDiff {
+	pub added: IndexMap<RuntimeID, String>,
+	pub removed: IndexMap<RuntimeID, String>,
+
+	/// RuntimeIDs whose path changed, with the old and new paths.
+	pub changed: IndexMap<RuntimeID, (String, String)>
+}
+
+/// Compare two hash lists.
+pub fn diff_hashes(old: &IndexMap<RuntimeID, String>, new: &IndexMap<RuntimeID, String>) -> HashesDiff {
+	let mut diff = HashesDiff::default();
+
+	for (&id, path) in old {
+		match new.get(&id) {
+			Some(new_path) if new_path != path => {
+				diff.changed.insert(id, (path.to_owned(), new_path.to_owned()));
+			}
+
+			Some(_) => {}
+
+			None => {
+				diff.removed.insert(id, path.to_owned());
+			}
+		}
+	}
+
+	for (&id, path) in new {
+		if !old.contains_key(&id) {
+			diff.added.insert(id, path.to_owned());
+		}
+	}
+
+	diff
 }
 
+#[try_fn]
+#[cfg_attr(feature = "rune", rune::function(keep))]
+pub fn parse_json_ores(bin_data: &[u8]) -> Result<String> {
+	read_string(&Bin1::parse(bin_data)?, 0)?
+}
+
+#[try_fn]
+#[cfg_attr(feature = "rune", rune::function(keep))]
+pub fn serialise_json_ores(data: &str) -> Result<Vec<u8>> {
+	let mut writer = Bin1Writer::new();
+
+	// The characters follow the string header and their size
+	writer.write_string_header(data.len(), 0x14)?;
+	writer.write_string_data(data)?;
+
+	writer.finish(8).generate()?
+}
+
+/// Parse a JSON ORES into a JSON value.
+#[cfg(feature = "serde")]
 #[try_fn]
 #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
-pub fn serialise_hashes_ores(data: &IndexMap<RuntimeID, String>) -> Result<Vec<u8>> {
-	let (hashes, values): (Vec<RuntimeID>, Vec<_>) = data.into_iter().unzip();
-
-	let mut ores = vec![];
-	let mut cursor = Cursor::new(&mut ores);
-
-	let start_of_strings = 0x30 + 0x18 * values.len();
-
-	let mut offsets = vec![0usize; values.len()];
-	let mut total_offset = 0;
-	for (i, value) in values.iter().enumerate() {
-		offsets[i] = total_offset;
-		total_offset += 4 + value.len() + 1;
-		total_offset += (4 - (value.len() + 1) % 4) % 4;
-	}
-
-	let end_of_strings =
-		start_of_strings + total_offset - (4 - (values.last().ok_or(OresError::ValuesEmpty)?.len() + 1) % 4) % 4;
-
-	cursor.write_all(b"\x42\x49\x4E\x31\x00\x08\x01\x00")?;
-	cursor.write_all(&(i32::try_from(end_of_strings)? - 0x10).to_be_bytes())?;
-	cursor.write_all(b"\x00\x00\x00\x00\x20\x00\x00\x00\x00\x00\x00\x00")?;
-	cursor.write_all(&(i32::try_from(start_of_strings)? - 0x10).to_le_bytes())?;
-	cursor.write_all(b"\x00\x00\x00\x00")?;
-	cursor.write_all(&(i32::try_from(start_of_strings)? - 0x10).to_le_bytes())?;
-	cursor.write_all(b"\x00\x00\x00\x00\x00\x00\x00\x00")?;
-	cursor.write_all(&(i32::try_from(values.len())?).to_le_bytes())?;
-
-	for (i, value) in values.iter().enumerate() {
-		cursor.write_all(&i32::try_from(value.len())?.to_le_bytes())?;
-		cursor.seek(SeekFrom::Current(-1))?;
-		cursor.write_all(b"\x40\x00\x00\x00\x00")?;
-		cursor.write_all(&i32::try_from(start_of_strings - 12 + offsets[i])?.to_le_bytes())?;
-		cursor.write_all(b"\x00\x00\x00\x00")?;
-
-		let hash_bytes = hashes[i].as_u64().to_be_bytes();
-		cursor.write_all(&[
-			hash_bytes[3],
-			hash_bytes[2],
-			hash_bytes[1],
-			hash_bytes[0],
-			hash_bytes[7],
-			hash_bytes[6],
-			hash_bytes[5],
-			hash_bytes[4]
-		])?;
-	}
-
-	for (i, value) in values.iter().enumerate() {
-		cursor.write_all(&i32::try_from(value.len() + 1)?.to_le_bytes())?;
-		cursor.write_all(value.as_bytes())?;
-		cursor.write_all(b"\x00")?;
-
-		if i != values.len() - 1 {
-			cursor.write_all(&vec![0u8; (4 - (value.len() + 1) % 4) % 4])?;
+pub fn parse_json_ores_value(bin_data: &[u8]) -> Result<serde_json::Value> {
+	serde_json::from_str(&parse_json_ores(bin_data)?)?
+}
+
+/// Parse a JSON ORES into a deserialisable type.
+#[cfg(feature = "serde")]
+#[try_fn]
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+pub fn parse_json_ores_as<T: serde::de::DeserializeOwned>(bin_data: &[u8]) -> Result<T> {
+	serde_json::from_str(&parse_json_ores(bin_data)?)?
+}
+
+/// Serialise a value into a JSON ORES. The JSON is written compactly, as in the game's files.
+#[cfg(feature = "serde")]
+#[try_fn]
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+pub fn serialise_json_ores_value<T: serde::Serialize + ?Sized>(data: &T) -> Result<Vec<u8>> {
+	serialise_json_ores(&serde_json::to_string(data)?)?
+}
+
+/// Apply a JSON Merge Patch (RFC 7386) to a JSON value.
+///
+/// Objects in the patch are merged recursively, `null` removes a key and any other value replaces the target outright.
+#[cfg(feature = "serde")]
+pub fn merge_json_patch(target: &mut serde_json::Value, patch: &serde_json::Value) {
+	let serde_json::Value::Object(patch) = patch else {
+		*target = patch.to_owned();
+		return;
+	};
+
+	if !target.is_object() {
+		*target = serde_json::Value::Object(Default::default());
+	}
+
+	let target = target.as_object_mut().unwrap();
+
+	for (key, value) in patch {
+		if value.is_null() {
+			target.remove(key);
+		} else {
+			merge_json_patch(target.entry(key).or_insert(serde_json::Value::Null), value);
 		}
 	}
+}
 
-	cursor.write_all(b"\xED\xA5\xEB\x12")?;
-	cursor.write_all(&i32::try_from(4 + (values.len() + 3) * 4)?.to_le_bytes())?;
-	cursor.write_all(&i32::try_from(values.len() + 3)?.to_le_bytes())?;
-	cursor.write_all(b"\x00\x00\x00\x00\x08\x00\x00\x00\x10\x00\x00\x00")?;
+/// Apply several JSON Merge Patches to a JSON ORES in order, so that later patches take precedence.
+#[cfg(feature = "serde")]
+#[try_fn]
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+pub fn merge_json_ores(bin_data: &[u8], patches: &[serde_json::Value]) -> Result<Vec<u8>> {
+	let mut value = parse_json_ores_value(bin_data)?;
 
-	for i in 0..values.len() {
-		cursor.write_all(&i32::try_from(40 + i * 24)?.to_le_bytes())?;
+	for patch in patches {
+		merge_json_patch(&mut value, patch);
 	}
 
-	ores
+	serialise_json_ores_value(&value)?
+}
+
+#[cfg(all(feature = "serde", feature = "rune"))]
+#[rune::function(path = merge_json_ores)]
+#[try_fn]
+fn r_merge_json_ores(bin_data: &[u8], patches: Vec<String>) -> Result<Vec<u8>> {
+	merge_json_ores(
+		bin_data,
+		&patches
+			.iter()
+			.map(|patch| serde_json::from_str(patch))
+			.collect::<Result<Vec<_>, _>>()?
+	)?
 }
 
 #[try_fn]
 #[cfg_attr(feature = "rune", rune::function(keep))]
-pub fn parse_json_ores(bin_data: &[u8]) -> Result<String> {
-	let mut cursor = Cursor::new(bin_data);
-	cursor.seek(SeekFrom::Start(36))?;
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+pub fn parse_string_array_ores(bin_data: &[u8]) -> Result<Vec<String>> {
+	let bin1 = Bin1::parse(bin_data)?;
 
-	let mut data = vec![0u8; bin_data.len() - 36 - 17];
-	cursor.read_exact(&mut data)?;
+	read_array(&bin1, 0, STRING_SIZE)?
+		.into_iter()
+		.map(|entry| read_string(&bin1, entry))
+		.collect::<Result<_, _>>()?
+}
 
-	String::from_utf8(data)?
+#[cfg(feature = "rune")]
+#[rune::function(path = serialise_string_array_ores)]
+fn r_serialise_string_array_ores(data: Vec<String>) -> Result<Vec<u8>> {
+	serialise_string_array_ores(&data)
+}
+
+#[try_fn]
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+pub fn serialise_string_array_ores(data: &[String]) -> Result<Vec<u8>> {
+	let mut writer = Bin1Writer::new();
+
+	let entries_start = writer.write_array_header(data.len(), STRING_SIZE as usize)?;
+
+	let mut string_offset = entries_start + STRING_SIZE * data.len() as u64;
+	for value in data {
+		writer.write_string_header(value.len(), string_offset + 4)?;
+
+		string_offset = (string_offset + 4 + value.len() as u64 + 1).next_multiple_of(4);
+	}
+
+	for value in data {
+		writer.write_string_data(value)?;
+		writer.align(4);
+	}
+
+	writer.finish(8).generate()?
 }
 
 #[try_fn]
 #[cfg_attr(feature = "rune", rune::function(keep))]
-pub fn serialise_json_ores(data: &str) -> Result<Vec<u8>> {
-	let mut ores = vec![];
-	let mut cursor = Cursor::new(&mut ores);
-
-	cursor.write_all(b"\x42\x49\x4E\x31\x00\x08\x01\x00")?;
-	cursor.write_all(&i32::try_from(data.len() + 21)?.to_be_bytes())?;
-	cursor.write_all(b"\x00\x00\x00\x00")?;
-	cursor.write_all(&i32::try_from(data.len() | 0x40000000)?.to_le_bytes())?;
-	cursor.write_all(b"\x00\x00\x00\x00\x14\x00\x00\x00\x00\x00\x00\x00")?;
-	cursor.write_all(&i32::try_from(data.len() + 1)?.to_le_bytes())?;
-	cursor.write_all(data.as_bytes())?;
-	cursor.write_all(b"\x00\xED\xA5\xEB\x12\x08\x00\x00\x00\x01\x00\x00\x00\x08\x00\x00\x00")?;
-
-	ores
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+pub fn parse_blob_list_ores(bin_data: &[u8]) -> Result<Vec<Vec<u8>>> {
+	let bin1 = Bin1::parse(bin_data)?;
+
+	read_array(&bin1, 0, ARRAY_SIZE)?
+		.into_iter()
+		.map(|entry| -> Result<Vec<u8>> {
+			let (begin, end) = bin1.read_array_range(entry)?;
+			Ok(bin1.read_bytes(begin, end - begin)?.to_vec())
+		})
+		.collect::<Result<_>>()?
+}
+
+#[cfg(feature = "rune")]
+#[rune::function(path = serialise_blob_list_ores)]
+fn r_serialise_blob_list_ores(data: Vec<Vec<u8>>) -> Result<Vec<u8>> {
+	serialise_blob_list_ores(&data)
+}
+
+#[try_fn]
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+pub fn serialise_blob_list_ores(data: &[Vec<u8>]) -> Result<Vec<u8>> {
+	let mut writer = Bin1Writer::new();
+
+	let entries_start = writer.write_array_header(data.len(), ARRAY_SIZE as usize)?;
+
+	// Each blob is preceded by its size, as with any other array
+	let mut blob_offset = entries_start + ARRAY_SIZE * data.len() as u64;
+	for blob in data {
+		writer.write_array_pointers(blob_offset + 4, blob_offset + 4 + blob.len() as u64)?;
+
+		blob_offset = (blob_offset + 4 + blob.len() as u64).next_multiple_of(4);
+	}
+
+	for blob in data {
+		writer.write_u32(u32::try_from(blob.len())?);
+		writer.write_bytes(blob);
+		writer.align(4);
+	}
+
+	writer.finish(8).generate()?
+}
+
+/// The layout of an ORES file's data.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "rune", derive(better_rune_derive::Any))]
+#[cfg_attr(feature = "rune", rune(item = ::glacier_formats::ores))]
+#[cfg_attr(feature = "rune", rune_derive(DEBUG_FMT, CLONE, PARTIAL_EQ, EQ))]
+pub enum OresKind {
+	/// A map of RuntimeIDs to their paths.
+	#[cfg_attr(feature = "rune", rune(constructor))]
+	Hashes,
+
+	/// A single JSON string. Configuration such as unlockables and scenarios is stored this way; its shape is only
+	/// known from the JSON itself.
+	#[cfg_attr(feature = "rune", rune(constructor))]
+	Json,
+
+	/// An array of strings.
+	#[cfg_attr(feature = "rune", rune(constructor))]
+	StringArray,
+
+	/// An array of binary blobs.
+	#[cfg_attr(feature = "rune", rune(constructor))]
+	BlobList,
+
+	/// An empty array, whose element type can't be determined from the data.
+	#[cfg_attr(feature = "rune", rune(constructor))]
+	EmptyArray
+}
+
+impl OresKind {
+	/// Detect the kind of an ORES from the shape of its data.
+	#[try_fn]
+	#[cfg_attr(feature = "rune", rune::function(keep, path = Self::detect))]
+	pub fn detect(bin_data: &[u8]) -> Result<Self> {
+		let bin1 = Bin1::parse(bin_data)?;
+
+		// A single string has its length (with the string flag) where an array would have its begin pointer
+		if !bin1.is_pointer(0) {
+			return if bin1.is_pointer(8) && read_string(&bin1, 0).is_ok() {
+				Ok(Self::Json)
+			} else {
+				Err(OresError::UnknownKind)
+			};
+		}
+
+		let (begin, end) = bin1.read_array_range(0)?;
+		let count = u64::from(bin1.read_u32(begin.checked_sub(4).ok_or(OresError::UnknownKind)?)?);
+		let size = end - begin;
+
+		if count == 0 && size == 0 {
+			Self::EmptyArray
+		} else if size == count * STRING_SIZE && bin1.is_pointer(begin + 8) {
+			Self::StringArray
+		} else if size == count * ARRAY_SIZE && bin1.is_pointer(begin) {
+			Self::BlobList
+		} else if size == count * HASHES_ENTRY_SIZE && bin1.is_pointer(begin + 8) {
+			Self::Hashes
+		} else {
+			Err(OresError::UnknownKind)?
+		}
+	}
+}
+
+/// A parsed ORES file of any supported kind.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "kind", content = "data"))]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "rune", derive(better_rune_derive::Any))]
+#[cfg_attr(feature = "rune", rune(item = ::glacier_formats::ores))]
+#[cfg_attr(feature = "rune", rune_derive(DEBUG_FMT, CLONE, PARTIAL_EQ))]
+#[cfg_attr(
+	feature = "rune",
+	rune_functions(
+		Self::parse__meta,
+		Self::kind__meta,
+		Self::generate__meta,
+		Self::rune_from_hash_list__meta,
+		Self::hash_list__meta
+	)
+)]
+pub enum Ores {
+	/// Use `Ores::from_hash_list` and `hash_list` from rune.
+	Hashes(IndexMap<RuntimeID, String>),
+
+	/// A JSON string, such as the unlockables or scenario configuration.
+	#[cfg_attr(feature = "rune", rune(constructor))]
+	Json(#[cfg_attr(feature = "rune", rune(get, set))] String),
+
+	#[cfg_attr(feature = "rune", rune(constructor))]
+	StringArray(#[cfg_attr(feature = "rune", rune(get, set))] Vec<String>),
+
+	#[cfg_attr(feature = "rune", rune(constructor))]
+	BlobList(#[cfg_attr(feature = "rune", rune(get, set))] Vec<Vec<u8>>),
+
+	/// An empty array. It's generated in the same way as an empty string array.
+	#[cfg_attr(feature = "rune", rune(constructor))]
+	EmptyArray
+}
+
+#[cfg(feature = "rune")]
+impl Ores {
+	#[rune::function(keep, path = Self::from_hash_list)]
+	fn rune_from_hash_list(data: HashList) -> Self {
+		Self::Hashes(data.0)
+	}
+
+	#[rune::function(keep, instance)]
+	fn hash_list(&self) -> Option<HashList> {
+		match self {
+			Self::Hashes(data) => Some(HashList(data.to_owned())),
+			_ => None
+		}
+	}
+}
+
+impl Ores {
+	/// Parse an ORES, detecting its kind.
+	#[try_fn]
+	#[cfg_attr(feature = "rune", rune::function(keep, path = Self::parse))]
+	#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+	pub fn parse(bin_data: &[u8]) -> Result<Self> {
+		match OresKind::detect(bin_data)? {
+			OresKind::Hashes => Self::Hashes(parse_hashes_ores(bin_data)?),
+			OresKind::Json => Self::Json(parse_json_ores(bin_data)?),
+			OresKind::StringArray => Self::StringArray(parse_string_array_ores(bin_data)?),
+			OresKind::BlobList => Self::BlobList(parse_blob_list_ores(bin_data)?),
+			OresKind::EmptyArray => Self::EmptyArray
+		}
+	}
+
+	#[cfg_attr(feature = "rune", rune::function(keep, instance))]
+	pub fn kind(&self) -> OresKind {
+		match self {
+			Self::Hashes(_) => OresKind::Hashes,
+			Self::Json(_) => OresKind::Json,
+			Self::StringArray(_) => OresKind::StringArray,
+			Self::BlobList(_) => OresKind::BlobList,
+			Self::EmptyArray => OresKind::EmptyArray
+		}
+	}
+
+	/// Generate the ORES file.
+	#[try_fn]
+	#[cfg_attr(feature = "rune", rune::function(keep, instance))]
+	#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+	pub fn generate(&self) -> Result<Vec<u8>> {
+		match self {
+			Self::Hashes(data) => serialise_hashes_ores(data)?,
+			Self::Json(data) => serialise_json_ores(data)?,
+			Self::StringArray(data) => serialise_string_array_ores(data)?,
+			Self::BlobList(data) => serialise_blob_list_ores(data)?,
+			Self::EmptyArray => serialise_string_array_ores(&[])?
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn hashes() -> IndexMap<RuntimeID, String> {
+		[
+			"[assembly:/_pro/characters/templates/hero/agent47/agent47.template?/agent47_default.entitytemplate].\
+			 pc_entitytype",
+			"[assembly:/_pro/scenes/frontend/mainmenu.entity].pc_entitytype",
+			"[assembly:/ui/a.json].pc_json"
+		]
+		.into_iter()
+		.map(|path| (hash_path(path), path.to_owned()))
+		.collect()
+	}
+
+	#[test]
+	fn hashes_round_trip() {
+		let data = serialise_hashes_ores(&hashes()).unwrap();
+
+		assert_eq!(parse_hashes_ores(&data).unwrap(), hashes());
+		assert_eq!(serialise_hashes_ores(&parse_hashes_ores(&data).unwrap()).unwrap(), data);
+		assert!(verify_hashes(&hashes()).is_empty());
+	}
+
+	#[test]
+	fn json_round_trip() {
+		let json = r#"{"Unlockables":[{"Id":"TOKEN_OUTFIT_HERO_QUIN_SUIT","Type":"disguise"}]}"#;
+		let data = serialise_json_ores(json).unwrap();
+
+		assert_eq!(parse_json_ores(&data).unwrap(), json);
+		assert_eq!(serialise_json_ores(&parse_json_ores(&data).unwrap()).unwrap(), data);
+	}
+
+	#[test]
+	fn string_array_round_trip() {
+		let strings = vec![
+			"a".to_owned(),
+			"four".to_owned(),
+			"a longer string".to_owned(),
+			String::new(),
+		];
+		let data = serialise_string_array_ores(&strings).unwrap();
+
+		assert_eq!(parse_string_array_ores(&data).unwrap(), strings);
+		assert_eq!(
+			serialise_string_array_ores(&parse_string_array_ores(&data).unwrap()).unwrap(),
+			data
+		);
+	}
+
+	#[test]
+	fn blob_list_round_trip() {
+		let blobs = vec![vec![1, 2, 3], vec![], vec![0xFF; 9]];
+		let data = serialise_blob_list_ores(&blobs).unwrap();
+
+		assert_eq!(parse_blob_list_ores(&data).unwrap(), blobs);
+		assert_eq!(
+			serialise_blob_list_ores(&parse_blob_list_ores(&data).unwrap()).unwrap(),
+			data
+		);
+	}
+
+	#[test]
+	fn bin1_errors_map_onto_ores_errors() {
+		assert!(matches!(parse_hashes_ores(b"BIN0\0\0\0\0"), Err(OresError::BadMagic)));
+	}
+
+	#[test]
+	fn detect_kinds() {
+		let cases = [
+			Ores::Hashes(hashes()),
+			Ores::Json("{}".into()),
+			Ores::StringArray(vec!["a".into(), "b".into()]),
+			Ores::BlobList(vec![vec![1, 2, 3, 4]]),
+			Ores::EmptyArray
+		];
+
+		for ores in cases {
+			let data = ores.generate().unwrap();
+
+			assert_eq!(OresKind::detect(&data).unwrap(), ores.kind());
+			assert_eq!(Ores::parse(&data).unwrap(), ores);
+			assert_eq!(Ores::parse(&data).unwrap().generate().unwrap(), data);
+		}
+	}
+
+	#[test]
+	fn detect_requires_exact_element_size() {
+		let mut bin1 = Bin1::parse(&serialise_string_array_ores(&["a".into(), "b".into()]).unwrap()).unwrap();
+
+		// Claim three entries for an array with room for two
+		let (begin, _) = bin1.read_array_range(0).unwrap();
+		bin1.data[begin as usize - 4..begin as usize].copy_from_slice(&3u32.to_le_bytes());
+
+		assert!(matches!(
+			OresKind::detect(&bin1.generate().unwrap()),
+			Err(OresError::UnknownKind)
+		));
+	}
+	#[test]
+	fn hash_path_known_id() {
+		let id: RuntimeID = "00D5CB7E6D04E32C".parse().unwrap();
+
+		assert_eq!(
+			hash_path("[assembly:/_pro/scenes/frontend/mainmenu.entity].pc_entitytype"),
+			id
+		);
+		assert_eq!(
+			hash_path("[ASSEMBLY:/_PRO/SCENES/FRONTEND/MAINMENU.ENTITY].PC_ENTITYTYPE"),
+			id
+		);
+	}
+
+	#[test]
+	fn verify_hashes_reports_mismatch() {
+		let wrong: RuntimeID = "001E589FC1C1C676".parse().unwrap();
+		let mut data = hashes();
+		data.insert(wrong, "[assembly:/ui/a.json].pc_json".into());
+
+		assert_eq!(
+			verify_hashes(&data),
+			vec![HashMismatch {
+				id: wrong,
+				path: "[assembly:/ui/a.json].pc_json".into(),
+				expected: "001E589FC1C1C675".parse().unwrap()
+			}]
+		);
+	}
+
+	#[test]
+	fn merge_hashes_prefers_earlier_lists() {
+		let id: RuntimeID = "001E589FC1C1C675".parse().unwrap();
+		let earlier = IndexMap::from([(id, "[assembly:/ui/a.json].pc_json".to_owned())]);
+		let later = IndexMap::from([
+			(id, "[assembly:/ui/b.json].pc_json".to_owned()),
+			(
+				hash_path("[assembly:/ui/c.json].pc_json"),
+				"[assembly:/ui/c.json].pc_json".to_owned()
+			)
+		]);
+
+		let (merged, conflicts) = merge_hashes([&earlier, &later]);
+
+		assert_eq!(merged.len(), 2);
+		assert_eq!(merged[&id], "[assembly:/ui/a.json].pc_json");
+		assert_eq!(
+			conflicts,
+			vec![HashConflict {
+				id,
+				existing: "[assembly:/ui/a.json].pc_json".into(),
+				incoming: "[assembly:/ui/b.json].pc_json".into()
+			}]
+		);
+	}
+
+	#[test]
+	fn diff_hashes_reports_changes() {
+		let kept: RuntimeID = "00D5CB7E6D04E32C".parse().unwrap();
+		let changed: RuntimeID = "001E589FC1C1C675".parse().unwrap();
+		let removed: RuntimeID = "0011111111111111".parse().unwrap();
+		let added: RuntimeID = "0022222222222222".parse().unwrap();
+
+		let old = IndexMap::from([
+			(kept, "kept".to_owned()),
+			(changed, "old".to_owned()),
+			(removed, "removed".to_owned())
+		]);
+		let new = IndexMap::from([
+			(kept, "kept".to_owned()),
+			(changed, "new".to_owned()),
+			(added, "added".to_owned())
+		]);
+
+		assert_eq!(
+			diff_hashes(&old, &new),
+			HashesDiff {
+				added: IndexMap::from([(added, "added".to_owned())]),
+				removed: IndexMap::from([(removed, "removed".to_owned())]),
+				changed: IndexMap::from([(changed, ("old".to_owned(), "new".to_owned()))])
+			}
+		);
+	}
 }
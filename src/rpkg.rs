@@ -0,0 +1,746 @@
+use std::{
+	fs::File,
+	io::{BufReader, Read, Seek, SeekFrom},
+	path::Path
+};
+
+use glacier_commons::metadata::{
+	FromU64Error, ReferenceFlags, ReferenceType, ResourceMetadata, ResourceReference, RuntimeID
+};
+use indexmap::IndexMap;
+use thiserror::Error;
+use tryvial::try_fn;
+
+type Result<T, E = RpkgError> = std::result::Result<T, E>;
+
+/// The bits of a resource's reference count used for flags.
+const REFERENCE_COUNT_FLAGS: u32 = 0xC0000000;
+
+/// The reference count flag set when reference flags use the V2 layout; otherwise they use the V1 layout.
+const V2_REFERENCE_FLAGS: u32 = 0x40000000;
+
+/// The key resources are XORed with when scrambled.
+const SCRAMBLE_KEY: [u8; 8] = [0xDC, 0x45, 0xA6, 0x9C, 0xD3, 0x72, 0x4C, 0xAB];
+
+#[derive(Error, Debug)]
+pub enum RpkgError {
+	#[error("io error: {0}")]
+	Io(#[from] std::io::Error),
+
+	#[error("invalid number: {0}")]
+	InvalidNumber(#[from] std::num::TryFromIntError),
+
+	#[error("not an RPKG file")]
+	BadMagic,
+
+	#[error("invalid RuntimeID: {0}")]
+	InvalidRuntimeID(#[from] FromU64Error),
+
+	#[error("invalid resource type: {0:?}")]
+	InvalidResourceType([u8; 4]),
+
+	#[error("decompression error: {0}")]
+	Decompression(#[from] lz4_flex::block::DecompressError),
+
+	#[error("deletions can only be written to a patch")]
+	DeletionsWithoutPatch
+}
+
+/// The version-specific header of an RPKG.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RpkgVersion {
+	/// Used by H1 and H2.
+	V1,
+
+	/// Used by H3.
+	V2(RpkgV2Header)
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RpkgV2Header {
+	pub unknown: u32,
+	pub chunk: u8,
+	pub chunk_type: u8,
+	pub patch: u8,
+	pub language_tag: [u8; 2]
+}
+
+/// A resource in an RPKG.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RpkgEntry {
+	pub metadata: ResourceMetadata,
+
+	/// The flag bits stored in the top two bits of the reference count, if the resource has references. These include
+	/// whether the reference flags use the V1 or V2 layout.
+	pub reference_count_flags: u32,
+
+	/// The offset of the resource's data in the RPKG.
+	pub offset: u64,
+
+	/// The size of the data as stored in the RPKG; 0 if the data isn't compressed.
+	pub compressed_size: u32,
+
+	/// The size of the data once decompressed.
+	pub size: u32,
+
+	pub system_memory_requirement: u32,
+	pub video_memory_requirement: u32
+}
+
+/// An RPKG archive, from which resources are read on demand. See [`RpkgBuilder`] for writing patches.
+#[derive(Debug)]
+pub struct Rpkg<R> {
+	pub version: RpkgVersion,
+	pub is_patch: bool,
+
+	/// Resources removed by this patch.
+	pub deletions: Vec<RuntimeID>,
+
+	pub entries: IndexMap<RuntimeID, RpkgEntry>,
+
+	reader: R
+}
+
+impl Rpkg<BufReader<File>> {
+	/// Open an RPKG file. See [`Self::parse`] for the meaning of `is_patch`.
+	#[try_fn]
+	pub fn open(path: impl AsRef<Path>, is_patch: bool) -> Result<Self> {
+		Self::parse(BufReader::new(File::open(path)?), is_patch)?
+	}
+}
+
+impl<R: Read + Seek> Rpkg<R> {
+	/// Read the header and resource index of an RPKG. `is_patch` is only used for V1 RPKGs, whose header doesn't say
+	/// whether they are a patch.
+	#[try_fn]
+	#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+	pub fn parse(mut reader: R, is_patch: bool) -> Result<Self> {
+		let version = match &read_bytes::<4>(&mut reader)? {
+			b"GKPR" => RpkgVersion::V1,
+
+			b"2KPR" => RpkgVersion::V2(RpkgV2Header {
+				unknown: read_u32(&mut reader)?,
+				chunk: read_bytes::<1>(&mut reader)?[0],
+				chunk_type: read_bytes::<1>(&mut reader)?[0],
+				patch: read_bytes::<1>(&mut reader)?[0],
+				language_tag: read_bytes::<2>(&mut reader)?
+			}),
+
+			_ => return Err(RpkgError::BadMagic)
+		};
+
+		let is_patch = match version {
+			RpkgVersion::V1 => is_patch,
+			RpkgVersion::V2(header) => header.patch != 0
+		};
+
+		let file_count = read_u32(&mut reader)?;
+		let _offset_table_size = read_u32(&mut reader)?;
+		let _header_table_size = read_u32(&mut reader)?;
+
+		let mut deletions = vec![];
+
+		if is_patch {
+			for _ in 0..read_u32(&mut reader)? {
+				deletions.push(read_u64(&mut reader)?.try_into()?);
+			}
+		}
+
+		let mut offsets = Vec::with_capacity(usize::try_from(file_count)?);
+
+		for _ in 0..file_count {
+			let id = RuntimeID::try_from(read_u64(&mut reader)?)?;
+			let offset = read_u64(&mut reader)?;
+			let size_and_flag = read_u32(&mut reader)?;
+
+			// The top bit marks scrambled data; the size is in the low 30 bits
+			offsets.push((id, offset, size_and_flag & 0x3FFFFFFF, size_and_flag & 0x80000000 != 0));
+		}
+
+		let mut entries = IndexMap::with_capacity(offsets.len());
+
+		for (id, offset, compressed_size, scrambled) in offsets {
+			// The type is stored reversed
+			let mut resource_type = read_bytes::<4>(&mut reader)?;
+			resource_type.reverse();
+
+			let references_size = read_u32(&mut reader)?;
+			let _states_chunk_size = read_u32(&mut reader)?;
+			let size = read_u32(&mut reader)?;
+			let system_memory_requirement = read_u32(&mut reader)?;
+			let video_memory_requirement = read_u32(&mut reader)?;
+
+			let mut references = vec![];
+			let mut reference_count_flags = 0;
+
+			if references_size > 0 {
+				let count_and_flags = read_u32(&mut reader)?;
+				reference_count_flags = count_and_flags & REFERENCE_COUNT_FLAGS;
+
+				let count = usize::try_from(count_and_flags & !REFERENCE_COUNT_FLAGS)?;
+
+				let mut flags = vec![0u8; count];
+				reader.read_exact(&mut flags)?;
+
+				for flags in flags {
+					references.push(ResourceReference {
+						resource: read_u64(&mut reader)?.try_into()?,
+						flags: if reference_count_flags & V2_REFERENCE_FLAGS != 0 {
+							reference_flags_from_byte(flags)
+						} else {
+							reference_flags_from_v1_byte(flags)
+						}
+					});
+				}
+			}
+
+			entries.insert(
+				id,
+				RpkgEntry {
+					metadata: ResourceMetadata {
+						id,
+						resource_type: std::str::from_utf8(&resource_type)
+							.ok()
+							.and_then(|x| x.try_into().ok())
+							.ok_or(RpkgError::InvalidResourceType(resource_type))?,
+						compressed: compressed_size != 0,
+						scrambled,
+						references
+					},
+					reference_count_flags,
+					offset,
+					compressed_size,
+					size,
+					system_memory_requirement,
+					video_memory_requirement
+				}
+			);
+		}
+
+		Self {
+			version,
+			is_patch,
+			deletions,
+			entries,
+			reader
+		}
+	}
+
+	/// Read a resource's data, decompressing and descrambling it as necessary.
+	#[try_fn]
+	#[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+	pub fn read_resource(&mut self, id: RuntimeID) -> Result<Option<(Vec<u8>, ResourceMetadata)>> {
+		let Some(entry) = self.entries.get(&id) else {
+			return Ok(None);
+		};
+
+		let stored_size = if entry.compressed_size != 0 {
+			entry.compressed_size
+		} else {
+			entry.size
+		};
+
+		self.reader.seek(SeekFrom::Start(entry.offset))?;
+
+		let mut data = vec![0u8; usize::try_from(stored_size)?];
+		self.reader.read_exact(&mut data)?;
+
+		if entry.metadata.scrambled {
+			scramble(&mut data);
+		}
+
+		if entry.compressed_size != 0 {
+			data = lz4_flex::block::decompress(&data, usize::try_from(entry.size)?)?;
+		}
+
+		Some((data, entry.metadata.to_owned()))
+	}
+}
+
+/// The chunk an RPKG belongs to and its patch number; e.g. chunk 0, patch 2 for `chunk0patch2.rpkg`. The base RPKG of a
+/// chunk has patch number 0.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RpkgLocation {
+	pub chunk: u32,
+	pub patch: u32
+}
+
+/// A game's RPKGs, read in patch order so that later patches override earlier ones.
+#[derive(Debug)]
+pub struct RpkgCollection<R> {
+	/// The RPKGs and their locations, from lowest to highest priority (e.g. `chunk0`, `chunk0patch1`, `chunk0patch2`,
+	/// `chunk1`).
+	pub rpkgs: Vec<(RpkgLocation, Rpkg<R>)>
+}
+
+impl<R: Read + Seek> RpkgCollection<R> {
+	pub fn new(rpkgs: Vec<(RpkgLocation, Rpkg<R>)>) -> Self {
+		Self { rpkgs }
+	}
+
+	/// Find the RPKG providing the current version of a resource. Returns `None` if the resource doesn't exist or has
+	/// been deleted by a patch.
+	///
+	/// A patch's deletions only apply to its own chunk: the base RPKG and lower-numbered patches of that chunk.
+	pub fn find(&self, id: RuntimeID) -> Option<usize> {
+		for (index, (location, rpkg)) in self.rpkgs.iter().enumerate().rev() {
+			if !rpkg.entries.contains_key(&id) {
+				continue;
+			}
+
+			let deleted = self.rpkgs.iter().any(|(other, other_rpkg)| {
+				other.chunk == location.chunk && other.patch > location.patch && other_rpkg.deletions.contains(&id)
+			});
+
+			if !deleted {
+				return Some(index);
+			}
+		}
+
+		None
+	}
+
+	/// Read the current version of a resource.
+	#[try_fn]
+	pub fn read_resource(&mut self, id: RuntimeID) -> Result<Option<(Vec<u8>, ResourceMetadata)>> {
+		match self.find(id) {
+			Some(index) => self.rpkgs[index].1.read_resource(id)?,
+			None => None
+		}
+	}
+}
+
+/// Builds a patch RPKG from resources, such as those returned by the `generate` functions.
+#[derive(Clone, Debug)]
+pub struct RpkgBuilder {
+	pub version: RpkgVersion,
+	pub resources: IndexMap<RuntimeID, (Vec<u8>, ResourceMetadata)>,
+
+	/// The reference count flags of resources, as read from an RPKG ([`RpkgEntry::reference_count_flags`]). Resources
+	/// not listed here use the usual flags of the version.
+	pub reference_count_flags: IndexMap<RuntimeID, u32>,
+
+	/// The video memory requirement written for each resource; `u32::MAX` for resources which aren't loaded into video
+	/// memory.
+	pub video_memory_requirement: u32,
+
+	/// Resources to remove from earlier RPKGs.
+	pub deletions: Vec<RuntimeID>
+}
+
+impl RpkgBuilder {
+	pub fn new(version: RpkgVersion) -> Self {
+		Self {
+			version,
+			resources: IndexMap::new(),
+			reference_count_flags: IndexMap::new(),
+			video_memory_requirement: u32::MAX,
+			deletions: vec![]
+		}
+	}
+
+	/// Add a resource, replacing any existing resource with the same ID.
+	pub fn add_resource(&mut self, data: Vec<u8>, metadata: ResourceMetadata) {
+		self.resources.insert(metadata.id, (data, metadata));
+	}
+
+	/// Mark a resource as deleted by this patch.
+	pub fn delete_resource(&mut self, id: RuntimeID) {
+		if !self.deletions.contains(&id) {
+			self.deletions.push(id);
+		}
+	}
+
+	/// Write the patch RPKG, compressing and scrambling each resource according to its metadata.
+	///
+	/// The system memory requirement of each resource is set to its size and the video memory requirement to
+	/// [`Self::video_memory_requirement`]. A V2 patch only has a deletion table if its header marks it as a patch.
+	#[try_fn]
+	#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+	pub fn generate(self) -> Result<Vec<u8>> {
+		let mut header_table = vec![];
+		let mut stored = vec![];
+
+		for (data, metadata) in self.resources.values() {
+			let mut resource_type = metadata.resource_type.to_string().into_bytes();
+			resource_type.reverse();
+			header_table.extend_from_slice(&resource_type);
+
+			let references_size = if metadata.references.is_empty() {
+				0
+			} else {
+				4 + metadata.references.len() * 9
+			};
+
+			header_table.extend_from_slice(&u32::try_from(references_size)?.to_le_bytes());
+			header_table.extend_from_slice(&0u32.to_le_bytes());
+			header_table.extend_from_slice(&u32::try_from(data.len())?.to_le_bytes());
+			header_table.extend_from_slice(&u32::try_from(data.len())?.to_le_bytes());
+			header_table.extend_from_slice(&self.video_memory_requirement.to_le_bytes());
+
+			if !metadata.references.is_empty() {
+				let reference_count_flags = match self.reference_count_flags.get(&metadata.id) {
+					Some(flags) => flags & REFERENCE_COUNT_FLAGS,
+					None => match self.version {
+						RpkgVersion::V1 => REFERENCE_COUNT_FLAGS & !V2_REFERENCE_FLAGS,
+						RpkgVersion::V2(_) => REFERENCE_COUNT_FLAGS
+					}
+				};
+
+				header_table.extend_from_slice(
+					&(u32::try_from(metadata.references.len())? | reference_count_flags).to_le_bytes()
+				);
+
+				for reference in &metadata.references {
+					header_table.push(if reference_count_flags & V2_REFERENCE_FLAGS != 0 {
+						reference_flags_to_byte(&reference.flags)
+					} else {
+						reference_flags_to_v1_byte(&reference.flags)
+					});
+				}
+
+				for reference in &metadata.references {
+					header_table.extend_from_slice(&reference.resource.as_u64().to_le_bytes());
+				}
+			}
+
+			let mut resource_data = if metadata.compressed {
+				lz4_flex::block::compress(data)
+			} else {
+				data.to_owned()
+			};
+
+			if metadata.scrambled {
+				scramble(&mut resource_data);
+			}
+
+			stored.push(resource_data);
+		}
+
+		let mut rpkg = vec![];
+
+		match self.version {
+			RpkgVersion::V1 => rpkg.extend_from_slice(b"GKPR"),
+
+			RpkgVersion::V2(header) => {
+				rpkg.extend_from_slice(b"2KPR");
+				rpkg.extend_from_slice(&header.unknown.to_le_bytes());
+				rpkg.extend_from_slice(&[header.chunk, header.chunk_type, header.patch]);
+				rpkg.extend_from_slice(&header.language_tag);
+			}
+		}
+
+		let offset_table_size = self.resources.len() * 20;
+
+		rpkg.extend_from_slice(&u32::try_from(self.resources.len())?.to_le_bytes());
+		rpkg.extend_from_slice(&u32::try_from(offset_table_size)?.to_le_bytes());
+		rpkg.extend_from_slice(&u32::try_from(header_table.len())?.to_le_bytes());
+
+		let is_patch = match self.version {
+			RpkgVersion::V1 => true,
+			RpkgVersion::V2(header) => header.patch != 0
+		};
+
+		if is_patch {
+			rpkg.extend_from_slice(&u32::try_from(self.deletions.len())?.to_le_bytes());
+			for deletion in &self.deletions {
+				rpkg.extend_from_slice(&deletion.as_u64().to_le_bytes());
+			}
+		} else if !self.deletions.is_empty() {
+			return Err(RpkgError::DeletionsWithoutPatch);
+		}
+
+		// Resource data follows both tables
+		let mut offset = (rpkg.len() + offset_table_size + header_table.len()) as u64;
+
+		for ((id, (_, metadata)), data) in self.resources.iter().zip(&stored) {
+			let compressed_size = if metadata.compressed {
+				u32::try_from(data.len())?
+			} else {
+				0
+			};
+
+			rpkg.extend_from_slice(&id.as_u64().to_le_bytes());
+			rpkg.extend_from_slice(&offset.to_le_bytes());
+			rpkg.extend_from_slice(&(compressed_size | if metadata.scrambled { 0x80000000 } else { 0 }).to_le_bytes());
+
+			offset += data.len() as u64;
+		}
+
+		rpkg.extend_from_slice(&header_table);
+
+		for data in stored {
+			rpkg.extend_from_slice(&data);
+		}
+
+		rpkg
+	}
+}
+
+/// Scramble or descramble data; the operation is its own inverse.
+fn scramble(data: &mut [u8]) {
+	for (i, byte) in data.iter_mut().enumerate() {
+		*byte ^= SCRAMBLE_KEY[i % SCRAMBLE_KEY.len()];
+	}
+}
+
+/// Decode the flags of a reference: the top two bits are the type, the next bit whether the reference is acquired and
+/// the rest the language code.
+fn reference_flags_from_byte(flags: u8) -> ReferenceFlags {
+	ReferenceFlags {
+		reference_type: match flags >> 6 {
+			0 => ReferenceType::Install,
+			1 => ReferenceType::Normal,
+			_ => ReferenceType::Weak
+		},
+		acquired: flags & 0x20 != 0,
+		language_code: flags & 0x1F
+	}
+}
+
+/// Encode the flags of a reference; the inverse of [`reference_flags_from_byte`].
+fn reference_flags_to_byte(flags: &ReferenceFlags) -> u8 {
+	let reference_type = match flags.reference_type {
+		ReferenceType::Install => 0,
+		ReferenceType::Normal => 1,
+		ReferenceType::Weak => 2
+	};
+
+	(reference_type << 6) | ((flags.acquired as u8) << 5) | (flags.language_code & 0x1F)
+}
+
+/// Decode the flags of a reference in the V1 layout: the lowest bit is whether the reference is acquired, the next
+/// whether it is weak and bit 5 whether it is an install dependency. The streaming bits in between aren't kept, and V1
+/// references have no language.
+fn reference_flags_from_v1_byte(flags: u8) -> ReferenceFlags {
+	ReferenceFlags {
+		reference_type: if flags & 0x02 != 0 {
+			ReferenceType::Weak
+		} else if flags & 0x20 != 0 {
+			ReferenceType::Install
+		} else {
+			ReferenceType::Normal
+		},
+		acquired: flags & 0x01 != 0,
+		language_code: 0x1F
+	}
+}
+
+/// Encode the flags of a reference in the V1 layout; the inverse of [`reference_flags_from_v1_byte`].
+fn reference_flags_to_v1_byte(flags: &ReferenceFlags) -> u8 {
+	let reference_type = match flags.reference_type {
+		ReferenceType::Install => 0x20,
+		ReferenceType::Normal => 0,
+		ReferenceType::Weak => 0x02
+	};
+
+	reference_type | flags.acquired as u8
+}
+
+#[try_fn]
+fn read_bytes<const N: usize>(reader: &mut impl Read) -> Result<[u8; N]> {
+	let mut x = [0u8; N];
+	reader.read_exact(&mut x)?;
+	x
+}
+
+#[try_fn]
+fn read_u32(reader: &mut impl Read) -> Result<u32> {
+	u32::from_le_bytes(read_bytes(reader)?)
+}
+
+#[try_fn]
+fn read_u64(reader: &mut impl Read) -> Result<u64> {
+	u64::from_le_bytes(read_bytes(reader)?)
+}
+
+#[cfg(test)]
+mod tests {
+	use std::io::Cursor;
+
+	use super::*;
+
+	fn resource() -> (Vec<u8>, ResourceMetadata) {
+		(
+			b"a resource which compresses well, well, well, well, well".to_vec(),
+			ResourceMetadata {
+				id: "00A1595C0918E2C9".parse().unwrap(),
+				resource_type: "MATI".try_into().unwrap(),
+				compressed: true,
+				scrambled: true,
+				references: vec![
+					ResourceReference {
+						resource: "00F5817876E691F1".parse().unwrap(),
+						flags: ReferenceFlags {
+							reference_type: ReferenceType::Install,
+							acquired: false,
+							language_code: 0b0001_1111
+						}
+					},
+					ResourceReference {
+						resource: "00C2D5D1D40D9B6B".parse().unwrap(),
+						flags: ReferenceFlags {
+							reference_type: ReferenceType::Weak,
+							acquired: true,
+							language_code: 0b0001_1111
+						}
+					},
+				]
+			}
+		)
+	}
+
+	fn build(version: RpkgVersion) -> Vec<u8> {
+		let (data, metadata) = resource();
+
+		let mut builder = RpkgBuilder::new(version);
+		builder.add_resource(data, metadata);
+		builder.delete_resource("00B0D6D2D8DB3C27".parse().unwrap());
+		builder.generate().unwrap()
+	}
+
+	#[test]
+	fn v2_patch_is_read_from_header() {
+		let version = RpkgVersion::V2(RpkgV2Header {
+			patch: 1,
+			..Default::default()
+		});
+
+		let mut rpkg = Rpkg::parse(Cursor::new(build(version)), false).unwrap();
+
+		assert_eq!(rpkg.version, version);
+		assert!(rpkg.is_patch);
+		assert_eq!(rpkg.deletions, vec!["00B0D6D2D8DB3C27".parse().unwrap()]);
+		assert_eq!(rpkg.read_resource(resource().1.id).unwrap(), Some(resource()));
+	}
+
+	#[test]
+	fn builder_round_trip() {
+		let mut rpkg = Rpkg::parse(Cursor::new(build(RpkgVersion::V1)), true).unwrap();
+		let entry = rpkg.entries[&resource().1.id].to_owned();
+
+		assert_eq!(entry.reference_count_flags, 0x80000000);
+		assert_eq!(entry.size, resource().0.len() as u32);
+		assert_eq!(entry.system_memory_requirement, resource().0.len() as u32);
+		assert_eq!(entry.video_memory_requirement, u32::MAX);
+
+		let mut builder = RpkgBuilder::new(rpkg.version);
+		builder.deletions = rpkg.deletions.to_owned();
+
+		let (data, metadata) = rpkg.read_resource(resource().1.id).unwrap().unwrap();
+		builder.add_resource(data, metadata);
+
+		assert_eq!(builder.generate().unwrap(), build(RpkgVersion::V1));
+	}
+
+	#[test]
+	fn v2_reference_flags_round_trip() {
+		let (data, mut metadata) = resource();
+		metadata.references[1].flags.language_code = 3;
+
+		let mut builder = RpkgBuilder::new(RpkgVersion::V2(Default::default()));
+		builder.video_memory_requirement = 0x1000;
+		builder.add_resource(data.to_owned(), metadata.to_owned());
+
+		let mut rpkg = Rpkg::parse(Cursor::new(builder.generate().unwrap()), false).unwrap();
+		let entry = rpkg.entries[&metadata.id].to_owned();
+
+		assert_eq!(entry.reference_count_flags, REFERENCE_COUNT_FLAGS);
+		assert_eq!(entry.video_memory_requirement, 0x1000);
+		assert_eq!(rpkg.read_resource(metadata.id).unwrap(), Some((data, metadata)));
+	}
+
+	#[test]
+	fn reference_count_flags_are_kept() {
+		let (data, metadata) = resource();
+
+		// A V2 RPKG with references in the V1 layout
+		let mut builder = RpkgBuilder::new(RpkgVersion::V2(Default::default()));
+		builder.reference_count_flags.insert(metadata.id, 0x80000000);
+		builder.add_resource(data, metadata);
+
+		let generated = builder.generate().unwrap();
+		let mut rpkg = Rpkg::parse(Cursor::new(generated.to_owned()), false).unwrap();
+
+		assert_eq!(rpkg.entries[&resource().1.id].reference_count_flags, 0x80000000);
+		assert_eq!(rpkg.read_resource(resource().1.id).unwrap(), Some(resource()));
+
+		let mut builder = RpkgBuilder::new(rpkg.version);
+
+		let flags: Vec<_> = rpkg
+			.entries
+			.values()
+			.map(|x| (x.metadata.id, x.reference_count_flags))
+			.collect();
+
+		for (id, flags) in flags {
+			builder.reference_count_flags.insert(id, flags);
+
+			let (data, metadata) = rpkg.read_resource(id).unwrap().unwrap();
+			builder.add_resource(data, metadata);
+		}
+
+		assert_eq!(builder.generate().unwrap(), generated);
+	}
+
+	#[test]
+	fn deletions_only_apply_to_their_chunk() {
+		let (data, metadata) = resource();
+		let id = metadata.id;
+
+		let with_resource = || {
+			let mut builder = RpkgBuilder::new(RpkgVersion::V1);
+			builder.add_resource(data.to_owned(), metadata.to_owned());
+
+			// The builder only writes patches, so these have an empty deletion table
+			Rpkg::parse(Cursor::new(builder.generate().unwrap()), true).unwrap()
+		};
+
+		let deleting = || {
+			let mut builder = RpkgBuilder::new(RpkgVersion::V1);
+			builder.delete_resource(id);
+
+			Rpkg::parse(Cursor::new(builder.generate().unwrap()), true).unwrap()
+		};
+
+		let location = |chunk, patch| RpkgLocation { chunk, patch };
+
+		// A patch of chunk 0 deletes the resource from chunk 0, but not from chunk 1
+		let mut collection = RpkgCollection::new(vec![
+			(location(0, 0), with_resource()),
+			(location(1, 0), with_resource()),
+			(location(0, 1), deleting()),
+		]);
+
+		assert_eq!(collection.find(id), Some(1));
+		assert_eq!(collection.read_resource(id).unwrap(), Some(resource()));
+
+		collection.rpkgs.remove(1);
+		assert_eq!(collection.find(id), None);
+
+		// A later patch of the same chunk can add it back
+		collection.rpkgs.push((location(0, 2), with_resource()));
+		assert_eq!(collection.find(id), Some(2));
+	}
+
+	#[test]
+	fn v2_deletions_require_patch() {
+		assert!(matches!(
+			RpkgBuilder {
+				deletions: vec!["00B0D6D2D8DB3C27".parse().unwrap()],
+				..RpkgBuilder::new(RpkgVersion::V2(Default::default()))
+			}
+			.generate(),
+			Err(RpkgError::DeletionsWithoutPatch)
+		));
+	}
+
+	#[test]
+	fn v1_patch_is_given() {
+		let mut rpkg = Rpkg::parse(Cursor::new(build(RpkgVersion::V1)), true).unwrap();
+
+		assert!(rpkg.is_patch);
+		assert_eq!(rpkg.deletions.len(), 1);
+		assert_eq!(rpkg.read_resource(resource().1.id).unwrap(), Some(resource()));
+	}
+}
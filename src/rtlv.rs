@@ -0,0 +1,468 @@
+use glacier_commons::{
+	game::GlacierGame,
+	metadata::{ResourceMetadata, ResourceReference, RuntimeID}
+};
+use indexmap::IndexMap;
+use thiserror::Error;
+use tryvial::try_fn;
+
+use crate::bin1::{Bin1, Bin1Error, Bin1Writer};
+
+#[cfg(feature = "rune")]
+pub fn rune_module() -> Result<rune::Module, rune::ContextError> {
+	let mut module = rune::Module::with_crate_item("glacier_formats", ["rtlv"])?;
+
+	module.ty::<RtlvError>()?;
+	module.ty::<LocalisedVideo>()?;
+	module.ty::<VideoMap>()?;
+	module.ty::<SubtitleMap>()?;
+
+	Ok(module)
+}
+
+type Result<T, E = RtlvError> = std::result::Result<T, E>;
+
+/// The size of a string header.
+const STRING_SIZE: u64 = 0x10;
+
+/// The size of a RuntimeID; its low half is the index of the reference.
+const RUNTIME_ID_SIZE: u64 = 0x8;
+
+/// The offsets of the arrays of `SLocalizedVideoDataDecrypted`.
+const AUDIO_LANGUAGES: u64 = 0x0;
+const VIDEOS_PER_AUDIO_LANGUAGE: u64 = 0x18;
+const SUBTITLE_LANGUAGES: u64 = 0x30;
+const SUBTITLE_MARKUPS_PER_LANGUAGE: u64 = 0x48;
+
+/// The XTEA key used to encrypt subtitles in versions after H1.
+const SUBTITLE_KEY: [u32; 4] = [0x30F95282, 0x1F48C419, 0x295F8548, 0x2A78366D];
+const XTEA_DELTA: u32 = 0x9E3779B9;
+const XTEA_ROUNDS: u32 = 32;
+
+#[derive(Error, Debug)]
+#[cfg_attr(feature = "rune", derive(better_rune_derive::Any))]
+#[cfg_attr(feature = "rune", rune(item = ::glacier_formats::rtlv))]
+#[cfg_attr(feature = "rune", rune_derive(DISPLAY_FMT, DEBUG_FMT))]
+pub enum RtlvError {
+	#[error("invalid BIN1: {0}")]
+	Bin1(#[from] Bin1Error),
+
+	#[error("invalid number: {0}")]
+	InvalidNumber(#[from] std::num::TryFromIntError),
+
+	#[error("invalid utf-8: {0}")]
+	InvalidString(#[from] std::string::FromUtf8Error),
+
+	#[error("no such dependency index: {0}")]
+	InvalidDependency(usize),
+
+	#[error("{languages} languages but {values} values in the array at {offset:#x}")]
+	MismatchedLanguageCount {
+		offset: u64,
+		languages: usize,
+		values: usize
+	},
+
+	#[error("language {0} appears more than once")]
+	DuplicateLanguage(String),
+
+	#[error("encrypted subtitle of {0} bytes isn't a whole number of blocks")]
+	InvalidEncryptedLength(usize)
+}
+
+fn xtea_encrypt(data: &[u8]) -> Vec<u8> {
+	let mut data = data.to_vec();
+	data.resize(data.len().next_multiple_of(8), 0);
+
+	for block in data.chunks_exact_mut(8) {
+		let mut v0 = u32::from_le_bytes(block[0..4].try_into().unwrap());
+		let mut v1 = u32::from_le_bytes(block[4..8].try_into().unwrap());
+		let mut sum = 0u32;
+
+		for _ in 0..XTEA_ROUNDS {
+			v0 = v0.wrapping_add(
+				(((v1 << 4) ^ (v1 >> 5)).wrapping_add(v1)) ^ sum.wrapping_add(SUBTITLE_KEY[(sum & 3) as usize])
+			);
+			sum = sum.wrapping_add(XTEA_DELTA);
+			v1 = v1.wrapping_add(
+				(((v0 << 4) ^ (v0 >> 5)).wrapping_add(v0)) ^ sum.wrapping_add(SUBTITLE_KEY[((sum >> 11) & 3) as usize])
+			);
+		}
+
+		block[0..4].copy_from_slice(&v0.to_le_bytes());
+		block[4..8].copy_from_slice(&v1.to_le_bytes());
+	}
+
+	data
+}
+
+#[try_fn]
+fn xtea_decrypt(data: &[u8]) -> Result<Vec<u8>> {
+	if !data.len().is_multiple_of(8) {
+		return Err(RtlvError::InvalidEncryptedLength(data.len()));
+	}
+
+	let mut data = data.to_vec();
+
+	for block in data.chunks_exact_mut(8) {
+		let mut v0 = u32::from_le_bytes(block[0..4].try_into().unwrap());
+		let mut v1 = u32::from_le_bytes(block[4..8].try_into().unwrap());
+		let mut sum = XTEA_DELTA.wrapping_mul(XTEA_ROUNDS);
+
+		for _ in 0..XTEA_ROUNDS {
+			v1 = v1.wrapping_sub(
+				(((v0 << 4) ^ (v0 >> 5)).wrapping_add(v0)) ^ sum.wrapping_add(SUBTITLE_KEY[((sum >> 11) & 3) as usize])
+			);
+			sum = sum.wrapping_sub(XTEA_DELTA);
+			v0 = v0.wrapping_sub(
+				(((v1 << 4) ^ (v1 >> 5)).wrapping_add(v1)) ^ sum.wrapping_add(SUBTITLE_KEY[(sum & 3) as usize])
+			);
+		}
+
+		block[0..4].copy_from_slice(&v0.to_le_bytes());
+		block[4..8].copy_from_slice(&v1.to_le_bytes());
+	}
+
+	// The plaintext is padded with up to 7 nulls to a whole number of blocks
+	let padding = data.iter().rev().take(7).take_while(|&&x| x == 0).count();
+	data.truncate(data.len() - padding);
+
+	data
+}
+
+/// Read an array of languages and the array of values in parallel with it.
+#[try_fn]
+fn read_language_arrays<T>(
+	bin1: &Bin1,
+	languages_offset: u64,
+	values_offset: u64,
+	value_size: u64,
+	read_value: impl Fn(u64) -> Result<T>
+) -> Result<IndexMap<String, T>> {
+	let languages = bin1.read_array(languages_offset, STRING_SIZE)?;
+	let values = bin1.read_array(values_offset, value_size)?;
+
+	if languages.len() != values.len() {
+		return Err(RtlvError::MismatchedLanguageCount {
+			offset: values_offset,
+			languages: languages.len(),
+			values: values.len()
+		});
+	}
+
+	let mut map = IndexMap::with_capacity(languages.len());
+
+	for (language, value) in languages.into_iter().zip(values) {
+		let language = bin1.read_string(language)?;
+
+		if map.contains_key(&language) {
+			return Err(RtlvError::DuplicateLanguage(language));
+		}
+
+		map.insert(language, read_value(value)?);
+	}
+
+	map
+}
+
+/// A localised video; a parsed RTLV file.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "rune", derive(better_rune_derive::Any))]
+#[cfg_attr(feature = "rune", rune(item = ::glacier_formats::rtlv))]
+#[cfg_attr(feature = "rune", rune_derive(DEBUG_FMT, PARTIAL_EQ, EQ, CLONE))]
+#[cfg_attr(feature = "rune", rune_functions(Self::parse__meta, Self::generate__meta))]
+#[cfg_attr(feature = "rune", rune(install_with = Self::rune_install))]
+#[cfg_attr(feature = "rune", rune(constructor_fn = Self::rune_construct))]
+pub struct LocalisedVideo {
+	#[cfg_attr(feature = "rune", rune(get, set))]
+	pub id: RuntimeID,
+
+	/// The video (GFXV) to play for each audio language, along with its reference flags.
+	pub videos: IndexMap<String, ResourceReference>,
+
+	/// The subtitle markup for each subtitle language.
+	pub subtitles: IndexMap<String, String>
+}
+
+#[cfg(feature = "rune")]
+crate::rune_map::ordered_map!(
+	/// The videos of an RTLV by language, in order.
+	VideoMap,
+	String,
+	ResourceReference,
+	::glacier_formats::rtlv
+);
+
+#[cfg(feature = "rune")]
+crate::rune_map::ordered_map!(
+	/// The subtitles of an RTLV by language, in order.
+	SubtitleMap,
+	String,
+	String,
+	::glacier_formats::rtlv
+);
+
+#[cfg(feature = "rune")]
+impl LocalisedVideo {
+	fn rune_construct(id: RuntimeID, videos: VideoMap, subtitles: SubtitleMap) -> Self {
+		Self {
+			id,
+			videos: videos.0,
+			subtitles: subtitles.0
+		}
+	}
+
+	fn rune_install(module: &mut rune::Module) -> Result<(), rune::ContextError> {
+		module.field_function(&rune::runtime::Protocol::GET, "videos", |s: &Self| {
+			VideoMap(s.videos.to_owned())
+		})?;
+
+		module.field_function(
+			&rune::runtime::Protocol::SET,
+			"videos",
+			|s: &mut Self, videos: VideoMap| {
+				s.videos = videos.0;
+			}
+		)?;
+
+		module.field_function(&rune::runtime::Protocol::GET, "subtitles", |s: &Self| {
+			SubtitleMap(s.subtitles.to_owned())
+		})?;
+
+		module.field_function(
+			&rune::runtime::Protocol::SET,
+			"subtitles",
+			|s: &mut Self, subtitles: SubtitleMap| {
+				s.subtitles = subtitles.0;
+			}
+		)?;
+
+		Ok(())
+	}
+}
+
+impl LocalisedVideo {
+	/// Parse an RTLV. Subtitles are decrypted in versions after H1.
+	///
+	/// The data is an `SLocalizedVideoDataDecrypted`: the audio languages, the video for each audio language, the
+	/// subtitle languages and the subtitle markup for each subtitle language, as four arrays.
+	#[try_fn]
+	#[cfg_attr(feature = "rune", rune::function(keep, path = Self::parse))]
+	#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+	pub fn parse(version: GlacierGame, rtlv_data: &[u8], rtlv_metadata: &ResourceMetadata) -> Result<Self> {
+		let bin1 = Bin1::parse(rtlv_data)?;
+
+		let videos = read_language_arrays(
+			&bin1,
+			AUDIO_LANGUAGES,
+			VIDEOS_PER_AUDIO_LANGUAGE,
+			RUNTIME_ID_SIZE,
+			|entry| {
+				// The GFXV is stored as a RuntimeID whose low half is the index of the reference
+				let dependency_index = usize::try_from(bin1.read_u32(entry + 4)?)?;
+
+				Ok(rtlv_metadata
+					.references
+					.get(dependency_index)
+					.ok_or(RtlvError::InvalidDependency(dependency_index))?
+					.to_owned())
+			}
+		)?;
+
+		let subtitles = read_language_arrays(
+			&bin1,
+			SUBTITLE_LANGUAGES,
+			SUBTITLE_MARKUPS_PER_LANGUAGE,
+			STRING_SIZE,
+			|entry| {
+				let markup = bin1.read_string_bytes(entry)?;
+
+				Ok(String::from_utf8(if version == GlacierGame::H1 {
+					markup.to_vec()
+				} else {
+					xtea_decrypt(markup)?
+				})?)
+			}
+		)?;
+
+		Self {
+			id: rtlv_metadata.id,
+			videos,
+			subtitles
+		}
+	}
+
+	/// Serialise this RTLV. Subtitles are encrypted in versions after H1.
+	#[try_fn]
+	#[cfg_attr(feature = "rune", rune::function(keep, instance))]
+	#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+	pub fn generate(self, version: GlacierGame) -> Result<(Vec<u8>, ResourceMetadata)> {
+		let mut writer = Bin1Writer::new();
+		let mut references = vec![];
+
+		// Strings are written after all four arrays, so their headers are patched afterwards
+		let mut strings = vec![];
+
+		for _ in 0..4 {
+			writer.write_array_pointers(0, 0)?;
+		}
+
+		let audio_languages_begin = writer.write_array_count(self.videos.len())?;
+
+		for language in self.videos.keys() {
+			strings.push((writer.position(), language.as_bytes().to_vec()));
+			writer.write_string_header(language.len(), 0)?;
+		}
+
+		writer.patch_array_pointers(AUDIO_LANGUAGES, audio_languages_begin, writer.position())?;
+
+		let videos_begin = writer.write_array_count(self.videos.len())?;
+
+		for video in self.videos.values() {
+			writer.write_u32(0);
+			writer.write_u32(u32::try_from(references.len())?);
+
+			references.push(video.to_owned());
+		}
+
+		writer.patch_array_pointers(VIDEOS_PER_AUDIO_LANGUAGE, videos_begin, writer.position())?;
+
+		let subtitle_languages_begin = writer.write_array_count(self.subtitles.len())?;
+
+		for language in self.subtitles.keys() {
+			strings.push((writer.position(), language.as_bytes().to_vec()));
+			writer.write_string_header(language.len(), 0)?;
+		}
+
+		writer.patch_array_pointers(SUBTITLE_LANGUAGES, subtitle_languages_begin, writer.position())?;
+
+		let markups_begin = writer.write_array_count(self.subtitles.len())?;
+
+		for markup in self.subtitles.values() {
+			let markup = if version == GlacierGame::H1 {
+				markup.as_bytes().to_vec()
+			} else {
+				xtea_encrypt(markup.as_bytes())
+			};
+
+			let header = writer.position();
+			writer.write_string_header(markup.len(), 0)?;
+			strings.push((header, markup));
+		}
+
+		writer.patch_array_pointers(SUBTITLE_MARKUPS_PER_LANGUAGE, markups_begin, writer.position())?;
+
+		for (header, string) in strings {
+			writer.align(4);
+
+			let position = writer.write_string_bytes(&string)?;
+			writer.patch_pointer(header + 8, position)?;
+		}
+
+		(
+			writer.finish(8).generate()?,
+			ResourceMetadata {
+				id: self.id,
+				resource_type: "RTLV".try_into().unwrap(),
+				compressed: ResourceMetadata::infer_compressed("RTLV".try_into().unwrap()),
+				scrambled: ResourceMetadata::infer_scrambled("RTLV".try_into().unwrap()),
+				references
+			}
+		)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use glacier_commons::metadata::{ReferenceFlags, ReferenceType};
+
+	use super::*;
+
+	fn video(resource: &str, flags: ReferenceFlags) -> ResourceReference {
+		ResourceReference {
+			resource: resource.parse().unwrap(),
+			flags
+		}
+	}
+
+	fn localised_video() -> LocalisedVideo {
+		LocalisedVideo {
+			id: "00B4F1E6A3E9C2D5".parse().unwrap(),
+			videos: IndexMap::from([
+				("en".into(), video("0090D4B8E1F6A2C3", ReferenceFlags::default())),
+				(
+					"fr".into(),
+					video(
+						"0090D4B8E1F6A2C3",
+						ReferenceFlags {
+							reference_type: ReferenceType::Weak,
+							acquired: true,
+							language_code: 2
+						}
+					)
+				),
+				(
+					"de".into(),
+					video(
+						"00E5A9C7D3B1F4A8",
+						ReferenceFlags {
+							reference_type: ReferenceType::Install,
+							acquired: false,
+							language_code: 3
+						}
+					)
+				)
+			]),
+			subtitles: IndexMap::from([
+				("en".into(), "<p begin=\"0.0\" end=\"2.5\">Good evening, 47.</p>".into()),
+				("fr".into(), "<p begin=\"0.0\" end=\"2.5\">Bonsoir, 47.</p>".into()),
+				("jp".into(), String::new())
+			])
+		}
+	}
+
+	#[test]
+	fn round_trip() {
+		for version in [GlacierGame::H1, GlacierGame::H3] {
+			let (data, metadata) = localised_video().generate(version).unwrap();
+			let parsed = LocalisedVideo::parse(version, &data, &metadata).unwrap();
+
+			assert_eq!(
+				metadata.references,
+				localised_video().videos.values().cloned().collect::<Vec<_>>()
+			);
+			assert_eq!(parsed, localised_video());
+
+			let (regenerated, regenerated_metadata) = parsed.generate(version).unwrap();
+			assert_eq!(regenerated, data);
+			assert_eq!(regenerated_metadata.references, metadata.references);
+		}
+	}
+
+	#[test]
+	fn subtitles_are_encrypted_after_h1() {
+		let markup = localised_video().subtitles["en"].to_owned();
+
+		let (h1, _) = localised_video().generate(GlacierGame::H1).unwrap();
+		let (h3, _) = localised_video().generate(GlacierGame::H3).unwrap();
+
+		assert!(h1.windows(markup.len()).any(|x| x == markup.as_bytes()));
+		assert!(!h3.windows(markup.len()).any(|x| x == markup.as_bytes()));
+
+		assert_eq!(
+			xtea_decrypt(&xtea_encrypt(markup.as_bytes())).unwrap(),
+			markup.as_bytes()
+		);
+	}
+
+	#[test]
+	fn decryption_only_strips_padding() {
+		// Only up to 7 bytes can be padding, so nulls before the last 7 bytes are kept
+		let plaintext = [b"<p>".as_slice(), &[0; 13]].concat();
+
+		assert_eq!(xtea_encrypt(&plaintext).len(), 16);
+		assert_eq!(xtea_decrypt(&xtea_encrypt(&plaintext)).unwrap(), plaintext[..9]);
+		assert_eq!(xtea_decrypt(&xtea_encrypt(b"<p>")).unwrap(), b"<p>");
+	}
+}
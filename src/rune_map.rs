@@ -0,0 +1,81 @@
+/// Define an insertion-ordered map type for rune, wrapping an `IndexMap<$key, $value>`.
+///
+/// Rune's own `HashMap` doesn't preserve order, so converting to and from it would reshuffle entries whenever a script
+/// regenerates a file. Maps are returned by value from getters, so modified maps need to be set back on their owner.
+macro_rules! ordered_map {
+	($(#[$meta:meta])* $name:ident, $key:ty, $value:ty, $($item:tt)+) => {
+		$(#[$meta])*
+		#[derive(Clone, Debug, Default, PartialEq, better_rune_derive::Any)]
+		#[rune(item = $($item)+)]
+		#[rune_derive(DEBUG_FMT, PARTIAL_EQ, CLONE)]
+		#[rune(constructor_fn = Self::new)]
+		#[rune_functions(
+			Self::get__meta,
+			Self::insert__meta,
+			Self::remove__meta,
+			Self::contains_key__meta,
+			Self::keys__meta,
+			Self::values__meta,
+			Self::iter__meta,
+			Self::len__meta,
+			Self::is_empty__meta
+		)]
+		pub struct $name(pub indexmap::IndexMap<$key, $value>);
+
+		impl $name {
+			fn new() -> Self {
+				Self::default()
+			}
+
+			#[rune::function(keep, instance)]
+			pub fn get(&self, key: $key) -> Option<$value> {
+				self.0.get(&key).cloned()
+			}
+
+			/// Insert a value, keeping the position of the key if it already exists.
+			#[rune::function(keep, instance)]
+			pub fn insert(&mut self, key: $key, value: $value) -> Option<$value> {
+				self.0.insert(key, value)
+			}
+
+			/// Remove a value, preserving the order of the remaining entries.
+			#[rune::function(keep, instance)]
+			pub fn remove(&mut self, key: $key) -> Option<$value> {
+				self.0.shift_remove(&key)
+			}
+
+			#[rune::function(keep, instance)]
+			pub fn contains_key(&self, key: $key) -> bool {
+				self.0.contains_key(&key)
+			}
+
+			#[rune::function(keep, instance)]
+			pub fn keys(&self) -> Vec<$key> {
+				self.0.keys().cloned().collect()
+			}
+
+			#[rune::function(keep, instance)]
+			pub fn values(&self) -> Vec<$value> {
+				self.0.values().cloned().collect()
+			}
+
+			/// Get the entries of the map as key-value pairs, in order.
+			#[rune::function(keep, instance)]
+			pub fn iter(&self) -> Vec<($key, $value)> {
+				self.0.iter().map(|(k, v)| (k.to_owned(), v.to_owned())).collect()
+			}
+
+			#[rune::function(keep, instance)]
+			pub fn len(&self) -> usize {
+				self.0.len()
+			}
+
+			#[rune::function(keep, instance)]
+			pub fn is_empty(&self) -> bool {
+				self.0.is_empty()
+			}
+		}
+	};
+}
+
+pub(crate) use ordered_map;
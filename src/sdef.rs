@@ -1,5 +1,4 @@
 use std::{
-	collections::HashMap,
 	io::{Cursor, Read},
 	str::FromStr
 };
@@ -20,6 +19,7 @@ pub fn rune_module() -> Result<rune::Module, rune::ContextError> {
 
 	module.ty::<SdefError>()?;
 	module.ty::<SoundDefinitions>()?;
+	module.ty::<SoundDefinitionMap>()?;
 	module.ty::<SoundDefinition>()?;
 
 	Ok(module)
@@ -70,29 +70,35 @@ pub struct SoundDefinitions {
 	pub definitions: IndexMap<String, Option<RuntimeID>>
 }
 
+#[cfg(feature = "rune")]
+crate::rune_map::ordered_map!(
+	/// The definitions of an SDEF, in order.
+	SoundDefinitionMap,
+	String,
+	Option<RuntimeID>,
+	::glacier_formats::sdef
+);
+
 #[cfg(feature = "rune")]
 impl SoundDefinitions {
-	fn rune_construct(id: RuntimeID, name: Option<String>, definitions: HashMap<String, Option<RuntimeID>>) -> Self {
+	fn rune_construct(id: RuntimeID, name: Option<String>, definitions: SoundDefinitionMap) -> Self {
 		Self {
 			id,
 			name,
-			definitions: definitions.into_iter().collect()
+			definitions: definitions.0
 		}
 	}
 
 	fn rune_install(module: &mut rune::Module) -> Result<(), rune::ContextError> {
 		module.field_function(&rune::runtime::Protocol::GET, "definitions", |s: &Self| {
-			s.definitions
-				.iter()
-				.map(|(k, v)| (k.to_string(), v.to_owned()))
-				.collect::<HashMap<_, _>>()
+			SoundDefinitionMap(s.definitions.to_owned())
 		})?;
 
 		module.field_function(
 			&rune::runtime::Protocol::SET,
 			"definitions",
-			|s: &mut Self, definitions: HashMap<String, Option<RuntimeID>>| {
-				s.definitions = definitions.into_iter().collect();
+			|s: &mut Self, definitions: SoundDefinitionMap| {
+				s.definitions = definitions.0;
 			}
 		)?;
 
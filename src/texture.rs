@@ -1,25 +1,355 @@
-use std::fmt::Display;
+use std::{fmt::Display, str::FromStr};
 
-use glacier_commons::metadata::RuntimeID;
+use glacier_commons::{
+	game::GlacierGame,
+	metadata::{ReferenceFlags, ReferenceType, ResourceMetadata, ResourceReference, RuntimeID}
+};
+use thiserror::Error;
+use tryvial::try_fn;
 
 #[cfg(feature = "rune")]
 pub fn rune_module() -> Result<rune::Module, rune::ContextError> {
 	let mut module = rune::Module::with_crate_item("glacier_formats", ["texture"])?;
 
+	module.ty::<TextureError>()?;
 	module.ty::<TextureMetadata>()?;
 	module.ty::<TextureType>()?;
 	module.ty::<RenderFormat>()?;
 	module.ty::<InterpretAs>()?;
+	module.ty::<ExportFormat>()?;
+	module.function_meta(r_convert_to_dds)?;
+	module.function_meta(r_convert_to_tga)?;
+	module.function_meta(convert_from_dds__meta)?;
+	module.function_meta(convert_from_tga__meta)?;
+	module.function_meta(r_export_image)?;
 
 	Ok(module)
 }
 
+type Result<T, E = TextureError> = std::result::Result<T, E>;
+
+#[derive(Error, Debug)]
+#[cfg_attr(feature = "rune", derive(better_rune_derive::Any))]
+#[cfg_attr(feature = "rune", rune(item = ::glacier_formats::texture))]
+#[cfg_attr(feature = "rune", rune_derive(DISPLAY_FMT, DEBUG_FMT))]
+pub enum TextureError {
+	#[error("texture conversion error: {0}")]
+	Conversion(String),
+
+	#[error("image error: {0}")]
+	Image(#[from] image::ImageError),
+
+	#[error("TEXD given but the TEXT has no references")]
+	MissingTexdReference,
+
+	#[error("texture has a TEXD but no TEXD ID was given")]
+	MissingTexdId,
+
+	#[error("texture type not supported by this library: {0}")]
+	UnsupportedTextureType(String),
+
+	#[error("render format not supported by this library: {0}")]
+	UnsupportedRenderFormat(String),
+
+	#[error("interpretation not supported by this library: {0}")]
+	UnsupportedInterpretAs(String),
+
+	#[error("invalid texture type: {0}")]
+	InvalidTextureType(String),
+
+	#[error("invalid render format: {0}")]
+	InvalidRenderFormat(String),
+
+	#[error("invalid interpretation: {0}")]
+	InvalidInterpretAs(String)
+}
+
+fn conversion_error(err: impl Display) -> TextureError {
+	TextureError::Conversion(err.to_string())
+}
+
+fn woa_version(version: GlacierGame) -> glacier_texture::WoaVersion {
+	match version {
+		GlacierGame::H1 => glacier_texture::WoaVersion::HM2016,
+		GlacierGame::H2 => glacier_texture::WoaVersion::HM2,
+		GlacierGame::H3 | GlacierGame::FL => glacier_texture::WoaVersion::HM3
+	}
+}
+
+#[try_fn]
+fn load_texture(
+	version: GlacierGame,
+	text_data: &[u8],
+	texd_data: Option<&[u8]>
+) -> Result<glacier_texture::texture_map::TextureMap> {
+	let version = woa_version(version);
+
+	let mut texture =
+		glacier_texture::texture_map::TextureMap::from_memory(text_data, version).map_err(conversion_error)?;
+
+	if let Some(texd_data) = texd_data {
+		texture.set_mipblock1(
+			glacier_texture::mipblock::MipblockData::from_memory(texd_data, version).map_err(conversion_error)?
+		);
+	}
+
+	texture
+}
+
+#[try_fn]
+fn pack_texture(
+	version: GlacierGame,
+	texture: &glacier_texture::texture_map::TextureMap
+) -> Result<(Vec<u8>, Option<Vec<u8>>)> {
+	let version = woa_version(version);
+
+	(
+		texture.pack_to_vec(version).map_err(conversion_error)?,
+		texture
+			.mipblock1()
+			.map(|texd| texd.pack_to_vec(version))
+			.transpose()
+			.map_err(conversion_error)?
+	)
+}
+
+#[cfg(feature = "rune")]
+#[rune::function(path = convert_to_dds)]
+fn r_convert_to_dds(
+	version: GlacierGame,
+	text_data: &[u8],
+	texd_data: Option<rune::runtime::Bytes>
+) -> Result<Vec<u8>> {
+	convert_to_dds(version, text_data, texd_data.as_deref())
+}
+
+/// Convert a TEXT (and its TEXD, if it has one) to a DDS file.
+#[try_fn]
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+pub fn convert_to_dds(version: GlacierGame, text_data: &[u8], texd_data: Option<&[u8]>) -> Result<Vec<u8>> {
+	glacier_texture::convert::create_dds(&load_texture(version, text_data, texd_data)?).map_err(conversion_error)?
+}
+
+#[cfg(feature = "rune")]
+#[rune::function(path = convert_to_tga)]
+fn r_convert_to_tga(
+	version: GlacierGame,
+	text_data: &[u8],
+	texd_data: Option<rune::runtime::Bytes>
+) -> Result<Vec<u8>> {
+	convert_to_tga(version, text_data, texd_data.as_deref())
+}
+
+/// Convert a TEXT (and its TEXD, if it has one) to a TGA file.
+#[try_fn]
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+pub fn convert_to_tga(version: GlacierGame, text_data: &[u8], texd_data: Option<&[u8]>) -> Result<Vec<u8>> {
+	glacier_texture::convert::create_tga(&load_texture(version, text_data, texd_data)?).map_err(conversion_error)?
+}
+
+/// Convert a DDS file to a TEXT and optional TEXD with the given metadata. The DDS is re-encoded if its format differs.
+#[try_fn]
+#[cfg_attr(feature = "rune", rune::function(keep))]
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+pub fn convert_from_dds(
+	version: GlacierGame,
+	dds_data: &[u8],
+	metadata: &TextureMetadata
+) -> Result<(Vec<u8>, Option<Vec<u8>>)> {
+	let texture = glacier_texture::texture_map::TextureMapBuilder::from_dds(dds_data)
+		.map_err(conversion_error)?
+		.format(metadata.format.into())
+		.texture_type(metadata.texture_type.into())
+		.interpret_as(metadata.interpret_as.into())
+		.build(woa_version(version))
+		.map_err(conversion_error)?;
+
+	pack_texture(version, &texture)?
+}
+
+/// Convert a TGA file to a TEXT and optional TEXD with the given metadata.
+#[try_fn]
+#[cfg_attr(feature = "rune", rune::function(keep))]
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+pub fn convert_from_tga(
+	version: GlacierGame,
+	tga_data: &[u8],
+	metadata: &TextureMetadata
+) -> Result<(Vec<u8>, Option<Vec<u8>>)> {
+	let texture = glacier_texture::texture_map::TextureMapBuilder::from_tga(tga_data)
+		.map_err(conversion_error)?
+		.format(metadata.format.into())
+		.texture_type(metadata.texture_type.into())
+		.interpret_as(metadata.interpret_as.into())
+		.build(woa_version(version))
+		.map_err(conversion_error)?;
+
+	pack_texture(version, &texture)?
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "rune", derive(better_rune_derive::Any))]
+#[cfg_attr(feature = "rune", rune(item = ::glacier_formats::texture))]
+#[cfg_attr(feature = "rune", rune_derive(DEBUG_FMT, CLONE, PARTIAL_EQ, EQ))]
+pub enum ExportFormat {
+	#[cfg_attr(feature = "rune", rune(constructor))]
+	Png,
+
+	#[cfg_attr(feature = "rune", rune(constructor))]
+	Tga,
+
+	#[cfg_attr(feature = "rune", rune(constructor))]
+	Dds
+}
+
+#[cfg(feature = "rune")]
+#[rune::function(path = export_image)]
+fn r_export_image(
+	version: GlacierGame,
+	text_data: &[u8],
+	texd_data: Option<rune::runtime::Bytes>,
+	format: ExportFormat
+) -> Result<Vec<u8>> {
+	export_image(version, text_data, texd_data.as_deref(), format)
+}
+
+/// Export a TEXT (and its TEXD, if it has one) as an image.
+///
+/// PNG and TGA exports are decoded according to the texture's format and interpretation; BC5 normal maps have their Z
+/// channel reconstructed, single-channel formats are expanded to greyscale and A8 is expanded to white with the stored
+/// alpha. Formats and interpretations not known to this library are exported as decoded. DDS exports are left as
+/// stored.
+#[try_fn]
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+pub fn export_image(
+	version: GlacierGame,
+	text_data: &[u8],
+	texd_data: Option<&[u8]>,
+	format: ExportFormat
+) -> Result<Vec<u8>> {
+	if format == ExportFormat::Dds {
+		return convert_to_dds(version, text_data, texd_data);
+	}
+
+	let texture = load_texture(version, text_data, texd_data)?;
+	let render_format = RenderFormat::try_from(texture.format()).ok();
+	let interpret_as = InterpretAs::try_from(texture.interpret_as()).ok();
+
+	let mut image = image::load_from_memory_with_format(
+		&glacier_texture::convert::create_tga(&texture).map_err(conversion_error)?,
+		image::ImageFormat::Tga
+	)?
+	.to_rgba8();
+
+	match render_format {
+		Some(RenderFormat::BC5) if matches!(interpret_as, Some(InterpretAs::Normal | InterpretAs::CompoundNormal)) => {
+			for pixel in image.pixels_mut() {
+				let x = pixel[0] as f32 / 127.5 - 1.0;
+				let y = pixel[1] as f32 / 127.5 - 1.0;
+				let z = (1.0 - x * x - y * y).max(0.0).sqrt();
+
+				pixel[2] = ((z + 1.0) * 127.5).round() as u8;
+				pixel[3] = u8::MAX;
+			}
+		}
+
+		Some(RenderFormat::BC4 | RenderFormat::R32) => {
+			for pixel in image.pixels_mut() {
+				pixel[1] = pixel[0];
+				pixel[2] = pixel[0];
+				pixel[3] = u8::MAX;
+			}
+		}
+
+		Some(RenderFormat::A8) => {
+			for pixel in image.pixels_mut() {
+				pixel[0] = u8::MAX;
+				pixel[1] = u8::MAX;
+				pixel[2] = u8::MAX;
+			}
+		}
+
+		_ => {}
+	}
+
+	let mut data = vec![];
+
+	image::DynamicImage::ImageRgba8(image).write_to(
+		&mut std::io::Cursor::new(&mut data),
+		match format {
+			ExportFormat::Png => image::ImageFormat::Png,
+			ExportFormat::Tga => image::ImageFormat::Tga,
+			ExportFormat::Dds => unreachable!()
+		}
+	)?;
+
+	data
+}
+
+/// Build an uncompressed RGBA DDS with a full mip chain from an image, so that it can be encoded to the target format.
+#[try_fn]
+fn build_mipped_dds(image: image::RgbaImage) -> Result<Vec<u8>> {
+	let (width, height) = image.dimensions();
+	let mip_count = 32 - width.max(height).max(1).leading_zeros();
+
+	let mut dds = vec![];
+	dds.extend_from_slice(b"DDS ");
+
+	for word in [
+		124,
+		// CAPS | HEIGHT | WIDTH | PITCH | PIXELFORMAT | MIPMAPCOUNT
+		0x2100F,
+		height,
+		width,
+		width * 4,
+		0,
+		mip_count
+	] {
+		dds.extend_from_slice(&u32::to_le_bytes(word));
+	}
+
+	dds.extend_from_slice(&[0; 11 * 4]);
+
+	// RGB | ALPHAPIXELS, 32 bits per pixel, RGBA8 masks
+	for word in [32, 0x41, 0, 32, 0xFF, 0xFF00, 0xFF0000, 0xFF000000] {
+		dds.extend_from_slice(&u32::to_le_bytes(word));
+	}
+
+	// TEXTURE | MIPMAP | COMPLEX
+	for word in [0x401008, 0, 0, 0, 0] {
+		dds.extend_from_slice(&u32::to_le_bytes(word));
+	}
+
+	let mut mip = image;
+
+	for _ in 0..mip_count {
+		dds.extend_from_slice(mip.as_raw());
+
+		let (width, height) = mip.dimensions();
+		mip = image::imageops::resize(
+			&mip,
+			(width / 2).max(1),
+			(height / 2).max(1),
+			image::imageops::FilterType::Triangle
+		);
+	}
+
+	dds
+}
+
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "rune", serde_with::apply(_ => #[rune(get, set)]))]
 #[cfg_attr(feature = "rune", derive(better_rune_derive::Any))]
 #[cfg_attr(feature = "rune", rune(item = ::glacier_formats::texture))]
 #[cfg_attr(feature = "rune", rune_derive(DEBUG_FMT, CLONE, PARTIAL_EQ, EQ))]
+#[cfg_attr(feature = "rune", rune(constructor))]
+#[cfg_attr(
+	feature = "rune",
+	rune_functions(Self::rune_from_resources__meta, Self::generate__meta, Self::import_image__meta)
+)]
 pub struct TextureMetadata {
 	pub text: RuntimeID,
 
@@ -36,6 +366,124 @@ pub struct TextureMetadata {
 	pub interpret_as: InterpretAs
 }
 
+impl TextureMetadata {
+	/// Read the metadata of a TEXT and its TEXD, if it has one. The TEXD's ID is taken from the TEXT's references.
+	#[try_fn]
+	#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+	pub fn from_resources(
+		text_data: &[u8],
+		text_metadata: &ResourceMetadata,
+		texd_data: Option<&[u8]>,
+		version: GlacierGame
+	) -> Result<Self> {
+		let texture = load_texture(version, text_data, texd_data)?;
+
+		let texd = text_metadata.references.first().map(|reference| reference.resource);
+
+		if texd_data.is_some() && texd.is_none() {
+			return Err(TextureError::MissingTexdReference);
+		}
+
+		Self {
+			text: text_metadata.id,
+			texd,
+			texture_type: texture.texture_type().try_into()?,
+			format: texture.format().try_into()?,
+			interpret_as: texture.interpret_as().try_into()?
+		}
+	}
+
+	/// Generate a TEXT and, if the texture has mips beyond the first, a TEXD from a DDS or TGA image.
+	#[try_fn]
+	#[cfg_attr(feature = "rune", rune::function(keep, instance))]
+	#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+	pub fn generate(
+		self,
+		version: GlacierGame,
+		image_data: &[u8]
+	) -> Result<((Vec<u8>, ResourceMetadata), Option<(Vec<u8>, ResourceMetadata)>)> {
+		let (text, texd) = if image_data.starts_with(b"DDS ") {
+			convert_from_dds(version, image_data, &self)?
+		} else {
+			convert_from_tga(version, image_data, &self)?
+		};
+
+		let texd = texd
+			.map(|texd| -> Result<_> {
+				Ok((
+					texd,
+					ResourceMetadata {
+						id: self.texd.ok_or(TextureError::MissingTexdId)?,
+						resource_type: "TEXD".try_into().unwrap(),
+						compressed: ResourceMetadata::infer_compressed("TEXD".try_into().unwrap()),
+						scrambled: ResourceMetadata::infer_scrambled("TEXD".try_into().unwrap()),
+						references: vec![]
+					}
+				))
+			})
+			.transpose()?;
+
+		(
+			(
+				text,
+				ResourceMetadata {
+					id: self.text,
+					resource_type: "TEXT".try_into().unwrap(),
+					compressed: ResourceMetadata::infer_compressed("TEXT".try_into().unwrap()),
+					scrambled: ResourceMetadata::infer_scrambled("TEXT".try_into().unwrap()),
+					references: texd
+						.as_ref()
+						.map(|(_, texd_metadata)| ResourceReference {
+							resource: texd_metadata.id,
+							flags: ReferenceFlags {
+								reference_type: ReferenceType::Normal,
+								acquired: false,
+								language_code: 0b0001_1111
+							}
+						})
+						.into_iter()
+						.collect()
+				}
+			),
+			texd
+		)
+	}
+
+	/// Replace a texture with an image, generating the TEXT and TEXD according to this metadata.
+	///
+	/// DDS images are used as-is (re-encoded if their format differs). Other images (e.g. PNG or TGA) are decoded and
+	/// given a full mip chain before being encoded.
+	#[try_fn]
+	#[cfg_attr(feature = "rune", rune::function(keep, instance))]
+	#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+	pub fn import_image(
+		self,
+		version: GlacierGame,
+		image_data: &[u8]
+	) -> Result<((Vec<u8>, ResourceMetadata), Option<(Vec<u8>, ResourceMetadata)>)> {
+		if image_data.starts_with(b"DDS ") {
+			return self.generate(version, image_data);
+		}
+
+		let image = image::load_from_memory(image_data)?.to_rgba8();
+
+		self.generate(version, &build_mipped_dds(image)?)?
+	}
+}
+
+#[cfg(feature = "rune")]
+impl TextureMetadata {
+	#[rune::function(keep, path = Self::from_resources)]
+	fn rune_from_resources(
+		text_data: &[u8],
+		text_metadata: &ResourceMetadata,
+		texd_data: Option<rune::runtime::Bytes>,
+		version: GlacierGame
+	) -> Result<Self> {
+		Self::from_resources(text_data, text_metadata, texd_data.as_deref(), version)
+	}
+}
+
 #[cfg(feature = "serde")]
 fn is_default<T: Default + PartialEq>(t: &T) -> bool {
 	t == &T::default()
@@ -46,19 +494,67 @@ fn is_default<T: Default + PartialEq>(t: &T) -> bool {
 #[cfg_attr(feature = "rune", derive(better_rune_derive::Any))]
 #[cfg_attr(feature = "rune", rune(item = ::glacier_formats::texture))]
 #[cfg_attr(feature = "rune", rune_derive(DISPLAY_FMT, DEBUG_FMT, CLONE, PARTIAL_EQ, EQ))]
+#[cfg_attr(feature = "rune", rune_functions(Self::rune_from_str__meta))]
 pub enum TextureType {
+	#[cfg_attr(feature = "rune", rune(constructor))]
 	Colour,
+
+	#[cfg_attr(feature = "rune", rune(constructor))]
 	Normal,
+
+	#[cfg_attr(feature = "rune", rune(constructor))]
 	Height,
+
+	#[cfg_attr(feature = "rune", rune(constructor))]
 	CompoundNormal,
+
+	#[cfg_attr(feature = "rune", rune(constructor))]
 	Billboard,
+
+	#[cfg_attr(feature = "rune", rune(constructor))]
 	Projection,
+
+	#[cfg_attr(feature = "rune", rune(constructor))]
 	Emission,
+
+	#[cfg_attr(feature = "rune", rune(constructor))]
 	Cubemap,
+
+	#[cfg_attr(feature = "rune", rune(constructor))]
 	UNKNOWN5,
+
+	#[cfg_attr(feature = "rune", rune(constructor))]
 	UNKNOWN517
 }
 
+impl FromStr for TextureType {
+	type Err = TextureError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"Colour" => Ok(Self::Colour),
+			"Normal" => Ok(Self::Normal),
+			"Height" => Ok(Self::Height),
+			"CompoundNormal" => Ok(Self::CompoundNormal),
+			"Billboard" => Ok(Self::Billboard),
+			"Projection" => Ok(Self::Projection),
+			"Emission" => Ok(Self::Emission),
+			"Cubemap" => Ok(Self::Cubemap),
+			"UNKNOWN5" => Ok(Self::UNKNOWN5),
+			"UNKNOWN517" => Ok(Self::UNKNOWN517),
+			_ => Err(TextureError::InvalidTextureType(s.into()))
+		}
+	}
+}
+
+#[cfg(feature = "rune")]
+impl TextureType {
+	#[rune::function(keep, path = Self::from_str)]
+	fn rune_from_str(s: &str) -> Result<Self> {
+		s.parse()
+	}
+}
+
 impl Display for TextureType {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		match self {
@@ -93,9 +589,11 @@ impl From<TextureType> for glacier_texture::enums::TextureType {
 	}
 }
 
-impl From<glacier_texture::enums::TextureType> for TextureType {
-	fn from(value: glacier_texture::enums::TextureType) -> Self {
-		match value {
+impl TryFrom<glacier_texture::enums::TextureType> for TextureType {
+	type Error = TextureError;
+
+	fn try_from(value: glacier_texture::enums::TextureType) -> Result<Self, Self::Error> {
+		Ok(match value {
 			glacier_texture::enums::TextureType::Colour => Self::Colour,
 			glacier_texture::enums::TextureType::Normal => Self::Normal,
 			glacier_texture::enums::TextureType::Height => Self::Height,
@@ -106,8 +604,8 @@ impl From<glacier_texture::enums::TextureType> for TextureType {
 			glacier_texture::enums::TextureType::Cubemap => Self::Cubemap,
 			glacier_texture::enums::TextureType::UNKNOWN5 => Self::UNKNOWN5,
 			glacier_texture::enums::TextureType::UNKNOWN517 => Self::UNKNOWN517,
-			_ => panic!("Unknown TextureType value {value:?}")
-		}
+			_ => return Err(TextureError::UnsupportedTextureType(format!("{value:?}")))
+		})
 	}
 }
 
@@ -116,24 +614,80 @@ impl From<glacier_texture::enums::TextureType> for TextureType {
 #[cfg_attr(feature = "rune", derive(better_rune_derive::Any))]
 #[cfg_attr(feature = "rune", rune(item = ::glacier_formats::texture))]
 #[cfg_attr(feature = "rune", rune_derive(DISPLAY_FMT, DEBUG_FMT, CLONE, PARTIAL_EQ, EQ))]
+#[cfg_attr(feature = "rune", rune_functions(Self::rune_from_str__meta))]
 pub enum RenderFormat {
+	#[cfg_attr(feature = "rune", rune(constructor))]
 	R32G32B32A32,
+
+	#[cfg_attr(feature = "rune", rune(constructor))]
 	R16G16B16A16,
+
+	#[cfg_attr(feature = "rune", rune(constructor))]
 	R8G8B8A8,
+
+	#[cfg_attr(feature = "rune", rune(constructor))]
 	R32,
+
+	#[cfg_attr(feature = "rune", rune(constructor))]
 	R8G8,
+
+	#[cfg_attr(feature = "rune", rune(constructor))]
 	A8,
+
+	#[cfg_attr(feature = "rune", rune(constructor))]
 	BC1,
+
+	#[cfg_attr(feature = "rune", rune(constructor))]
 	BC2,
+
+	#[cfg_attr(feature = "rune", rune(constructor))]
 	BC3,
+
+	#[cfg_attr(feature = "rune", rune(constructor))]
 	BC4,
+
+	#[cfg_attr(feature = "rune", rune(constructor))]
 	BC5,
+
+	#[cfg_attr(feature = "rune", rune(constructor))]
 	BC6,
 
 	#[default]
+	#[cfg_attr(feature = "rune", rune(constructor))]
 	BC7
 }
 
+impl FromStr for RenderFormat {
+	type Err = TextureError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"R32G32B32A32" => Ok(Self::R32G32B32A32),
+			"R16G16B16A16" => Ok(Self::R16G16B16A16),
+			"R8G8B8A8" => Ok(Self::R8G8B8A8),
+			"R32" => Ok(Self::R32),
+			"R8G8" => Ok(Self::R8G8),
+			"A8" => Ok(Self::A8),
+			"BC1" => Ok(Self::BC1),
+			"BC2" => Ok(Self::BC2),
+			"BC3" => Ok(Self::BC3),
+			"BC4" => Ok(Self::BC4),
+			"BC5" => Ok(Self::BC5),
+			"BC6" => Ok(Self::BC6),
+			"BC7" => Ok(Self::BC7),
+			_ => Err(TextureError::InvalidRenderFormat(s.into()))
+		}
+	}
+}
+
+#[cfg(feature = "rune")]
+impl RenderFormat {
+	#[rune::function(keep, path = Self::from_str)]
+	fn rune_from_str(s: &str) -> Result<Self> {
+		s.parse()
+	}
+}
+
 impl Display for RenderFormat {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		match self {
@@ -174,9 +728,11 @@ impl From<RenderFormat> for glacier_texture::enums::RenderFormat {
 	}
 }
 
-impl From<glacier_texture::enums::RenderFormat> for RenderFormat {
-	fn from(value: glacier_texture::enums::RenderFormat) -> Self {
-		match value {
+impl TryFrom<glacier_texture::enums::RenderFormat> for RenderFormat {
+	type Error = TextureError;
+
+	fn try_from(value: glacier_texture::enums::RenderFormat) -> Result<Self, Self::Error> {
+		Ok(match value {
 			glacier_texture::enums::RenderFormat::R32G32B32A32 => Self::R32G32B32A32,
 			glacier_texture::enums::RenderFormat::R16G16B16A16 => Self::R16G16B16A16,
 			glacier_texture::enums::RenderFormat::R8G8B8A8 => Self::R8G8B8A8,
@@ -190,8 +746,8 @@ impl From<glacier_texture::enums::RenderFormat> for RenderFormat {
 			glacier_texture::enums::RenderFormat::BC5 => Self::BC5,
 			glacier_texture::enums::RenderFormat::BC6 => Self::BC6,
 			glacier_texture::enums::RenderFormat::BC7 => Self::BC7,
-			_ => panic!("Unknown RenderFormat value {value:?}")
-		}
+			_ => return Err(TextureError::UnsupportedRenderFormat(format!("{value:?}")))
+		})
 	}
 }
 
@@ -200,20 +756,60 @@ impl From<glacier_texture::enums::RenderFormat> for RenderFormat {
 #[cfg_attr(feature = "rune", derive(better_rune_derive::Any))]
 #[cfg_attr(feature = "rune", rune(item = ::glacier_formats::texture))]
 #[cfg_attr(feature = "rune", rune_derive(DISPLAY_FMT, DEBUG_FMT, CLONE, PARTIAL_EQ, EQ))]
+#[cfg_attr(feature = "rune", rune_functions(Self::rune_from_str__meta))]
 pub enum InterpretAs {
+	#[cfg_attr(feature = "rune", rune(constructor))]
 	Colour,
 
 	#[default]
+	#[cfg_attr(feature = "rune", rune(constructor))]
 	Normal,
 
+	#[cfg_attr(feature = "rune", rune(constructor))]
 	Height,
+
+	#[cfg_attr(feature = "rune", rune(constructor))]
 	CompoundNormal,
+
+	#[cfg_attr(feature = "rune", rune(constructor))]
 	Billboard,
+
+	#[cfg_attr(feature = "rune", rune(constructor))]
 	Cubemap,
+
+	#[cfg_attr(feature = "rune", rune(constructor))]
 	Emission,
+
+	#[cfg_attr(feature = "rune", rune(constructor))]
 	Volume
 }
 
+impl FromStr for InterpretAs {
+	type Err = TextureError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"Colour" => Ok(Self::Colour),
+			"Normal" => Ok(Self::Normal),
+			"Height" => Ok(Self::Height),
+			"CompoundNormal" => Ok(Self::CompoundNormal),
+			"Billboard" => Ok(Self::Billboard),
+			"Cubemap" => Ok(Self::Cubemap),
+			"Emission" => Ok(Self::Emission),
+			"Volume" => Ok(Self::Volume),
+			_ => Err(TextureError::InvalidInterpretAs(s.into()))
+		}
+	}
+}
+
+#[cfg(feature = "rune")]
+impl InterpretAs {
+	#[rune::function(keep, path = Self::from_str)]
+	fn rune_from_str(s: &str) -> Result<Self> {
+		s.parse()
+	}
+}
+
 impl Display for InterpretAs {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		match self {
@@ -244,9 +840,11 @@ impl From<InterpretAs> for glacier_texture::enums::InterpretAs {
 	}
 }
 
-impl From<glacier_texture::enums::InterpretAs> for InterpretAs {
-	fn from(value: glacier_texture::enums::InterpretAs) -> Self {
-		match value {
+impl TryFrom<glacier_texture::enums::InterpretAs> for InterpretAs {
+	type Error = TextureError;
+
+	fn try_from(value: glacier_texture::enums::InterpretAs) -> Result<Self, Self::Error> {
+		Ok(match value {
 			glacier_texture::enums::InterpretAs::Colour => Self::Colour,
 			glacier_texture::enums::InterpretAs::Normal => Self::Normal,
 			glacier_texture::enums::InterpretAs::Height => Self::Height,
@@ -254,7 +852,80 @@ impl From<glacier_texture::enums::InterpretAs> for InterpretAs {
 			glacier_texture::enums::InterpretAs::Billboard => Self::Billboard,
 			glacier_texture::enums::InterpretAs::Cubemap => Self::Cubemap,
 			glacier_texture::enums::InterpretAs::Emission => Self::Emission,
-			glacier_texture::enums::InterpretAs::Volume => Self::Volume
-		}
+			glacier_texture::enums::InterpretAs::Volume => Self::Volume,
+			// Currently exhaustive, but glacier-texture may gain variants
+			#[allow(unreachable_patterns)]
+			_ => return Err(TextureError::UnsupportedInterpretAs(format!("{value:?}")))
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn generate_round_trip() {
+		let metadata = TextureMetadata {
+			text: "00C2D5D1D40D9B6B".parse().unwrap(),
+			texd: Some("0042AFDB1D1E8F39".parse().unwrap()),
+			texture_type: TextureType::Colour,
+			format: RenderFormat::R8G8B8A8,
+			interpret_as: InterpretAs::Colour
+		};
+
+		let image = image::RgbaImage::from_fn(8, 8, |x, y| image::Rgba([(x * 32) as u8, (y * 32) as u8, 128, 255]));
+
+		let ((text, text_metadata), texd) = metadata
+			.clone()
+			.generate(GlacierGame::H3, &build_mipped_dds(image).unwrap())
+			.unwrap();
+		let texd_data = texd.as_ref().map(|(data, _)| data.as_slice());
+
+		assert_eq!(
+			TextureMetadata::from_resources(&text, &text_metadata, texd_data, GlacierGame::H3).unwrap(),
+			TextureMetadata {
+				texd: texd.as_ref().map(|(_, texd_metadata)| texd_metadata.id),
+				..metadata.clone()
+			}
+		);
+
+		let dds = convert_to_dds(GlacierGame::H3, &text, texd_data).unwrap();
+		let ((regenerated, _), regenerated_texd) = metadata.generate(GlacierGame::H3, &dds).unwrap();
+
+		assert_eq!(regenerated, text);
+		assert_eq!(regenerated_texd.map(|(data, _)| data), texd.map(|(data, _)| data));
+	}
+
+	#[test]
+	fn export_a8_as_white_with_alpha() {
+		let metadata = TextureMetadata {
+			text: "00C2D5D1D40D9B6B".parse().unwrap(),
+			texd: None,
+			texture_type: TextureType::Colour,
+			format: RenderFormat::A8,
+			interpret_as: InterpretAs::Colour
+		};
+
+		let image = image::RgbaImage::from_fn(8, 8, |x, _| image::Rgba([0, 0, 0, (x * 32) as u8]));
+
+		let ((text, _), texd) = metadata
+			.generate(GlacierGame::H3, &build_mipped_dds(image).unwrap())
+			.unwrap();
+
+		let png = export_image(
+			GlacierGame::H3,
+			&text,
+			texd.as_ref().map(|(data, _)| data.as_slice()),
+			ExportFormat::Png
+		)
+		.unwrap();
+
+		let exported = image::load_from_memory_with_format(&png, image::ImageFormat::Png)
+			.unwrap()
+			.to_rgba8();
+
+		assert!(exported.pixels().all(|x| x[0..3] == [u8::MAX; 3]));
+		assert!(exported.pixels().any(|x| x[3] != u8::MAX));
 	}
 }